@@ -0,0 +1,271 @@
+//! Run a WebAssembly auto splitter (`.wasm`) configured for the speedrun
+//!
+//! The auto splitter is run by the auto splitting runtime of livesplit-core.
+//! It controls the timer through the same timer controls as hotkeys, so the
+//! splits display is updated and practice mode is honored. Its status (loaded
+//! or unloaded with the reason) is shown in the splits display
+use crate::timer_controls::{
+    pause_game_time, reset_without_confirmation, resume_game_time, set_game_time,
+    start_or_split_timer, TimerControlError,
+};
+use crate::ui::Splits;
+use livesplit_auto_splitting::{time, Runtime, SettingsStore, TimerState};
+use livesplit_core::TimerPhase::{Ended, NotRunning, Paused, Running};
+use livesplit_core::{TimeSpan, Timer};
+use log::{error, info, warn};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time after which an update of the auto splitter is interrupted
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timer controlled by the auto splitter
+struct AutoSplitterTimer {
+    timer: Arc<RwLock<Timer>>,
+    splits: Arc<RwLock<Splits>>,
+}
+
+impl AutoSplitterTimer {
+    /// Returns phase of the timer
+    ///
+    /// # Panics
+    /// Panics if timer lock cannot be acquired
+    fn phase(&self) -> livesplit_core::TimerPhase {
+        match self.timer.read().map_err(TimerControlError::TimerReadLock) {
+            Ok(timer) => timer.current_phase(),
+            Err(e) => {
+                error!("{e}");
+                panic!("{e}") // cannot recover
+            }
+        }
+    }
+}
+
+impl livesplit_auto_splitting::Timer for AutoSplitterTimer {
+    fn state(&self) -> TimerState {
+        match self.phase() {
+            NotRunning => TimerState::NotRunning,
+            Running => TimerState::Running,
+            Paused => TimerState::Paused,
+            Ended => TimerState::Ended,
+        }
+    }
+
+    fn start(&mut self) {
+        if self.phase() == NotRunning {
+            info!("Auto splitter: start");
+            start_or_split_timer(&self.timer, &self.splits);
+        }
+    }
+
+    fn split(&mut self) {
+        if self.phase() == Running {
+            info!("Auto splitter: split");
+            start_or_split_timer(&self.timer, &self.splits);
+        }
+    }
+
+    fn reset(&mut self) {
+        info!("Auto splitter: reset");
+        reset_without_confirmation(&self.timer, &self.splits);
+    }
+
+    fn set_game_time(&mut self, time: time::Duration) {
+        set_game_time(&self.timer, TimeSpan::from(time));
+    }
+
+    fn pause_game_time(&mut self) {
+        pause_game_time(&self.timer);
+    }
+
+    fn resume_game_time(&mut self) {
+        resume_game_time(&self.timer);
+    }
+
+    fn set_variable(&mut self, name: &str, value: &str) {
+        match self
+            .timer
+            .write()
+            .map_err(TimerControlError::TimerWriteLock)
+        {
+            Ok(mut timer) => timer.set_custom_variable(name, value),
+            Err(e) => {
+                error!("{e}");
+                panic!("{e}") // cannot recover
+            }
+        }
+    }
+
+    fn log(&mut self, message: fmt::Arguments<'_>) {
+        info!("Auto splitter: {message}");
+    }
+}
+
+/// Load auto splitter at `path` and let it control `timer` until it fails.
+/// Status of the auto splitter is shown in `splits` display
+///
+/// # Panics
+/// Panics if splits lock cannot be acquired
+pub fn start_auto_splitter(path: PathBuf, timer: Arc<RwLock<Timer>>, splits: Arc<RwLock<Splits>>) {
+    thread::spawn(move || {
+        let status_splits = splits.clone();
+        let set_status = |status: String| {
+            match status_splits
+                .write()
+                .map_err(TimerControlError::SplitsWriteLock)
+            {
+                Ok(mut splits) => splits.set_auto_splitter_status(status),
+                Err(e) => {
+                    error!("{e}");
+                    panic!("{e}") // cannot recover
+                }
+            }
+        };
+        set_status("loading".to_string());
+        let module = match fs::read(&path) {
+            Ok(module) => module,
+            Err(e) => {
+                error!("Auto splitter \"{}\": {e}", path.display());
+                set_status(format!("unloaded ({e})"));
+                return;
+            }
+        };
+        let mut runtime = match Runtime::new(
+            &module,
+            AutoSplitterTimer { timer, splits },
+            SettingsStore::new(),
+        ) {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                error!(
+                    "Auto splitter \"{}\" could not be loaded: {e}",
+                    path.display()
+                );
+                set_status(format!("unloaded ({e})"));
+                return;
+            }
+        };
+        info!("Loaded auto splitter \"{}\"", path.display());
+        set_status("loaded".to_string());
+
+        // interrupt auto splitter stuck in an update (infinite loop...). The
+        // watchdog stops once the auto splitter thread drops `update_started`
+        let update_started: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+        let watched_update = Arc::downgrade(&update_started);
+        let interrupt_handle = runtime.interrupt_handle();
+        thread::spawn(move || loop {
+            thread::sleep(UPDATE_TIMEOUT);
+            let Some(watched_update) = watched_update.upgrade() else {
+                return;
+            };
+            // NOTE: a poisoned lock only means the auto splitter thread stopped
+            let started = *watched_update
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if started.is_some_and(|started| started.elapsed() > UPDATE_TIMEOUT) {
+                warn!("Auto splitter update takes too long, interrupting it");
+                interrupt_handle.interrupt();
+                return;
+            }
+        });
+
+        loop {
+            *update_started
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Instant::now());
+            let update = runtime.update();
+            *update_started
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+            match update {
+                Ok(tick_rate) => thread::sleep(tick_rate),
+                Err(e) => {
+                    error!("Auto splitter unloaded after failure: {e:?}");
+                    set_status(format!("unloaded ({e})"));
+                    return;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::timer_and_splits;
+    use livesplit_auto_splitting::Timer as _;
+    use livesplit_core::TimingMethod;
+
+    /// Returns auto splitter timer of a run with 2 splits
+    fn auto_splitter_timer() -> AutoSplitterTimer {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+        AutoSplitterTimer { timer, splits }
+    }
+
+    #[test]
+    fn split_is_ignored_before_start() {
+        let mut timer = auto_splitter_timer();
+        timer.split();
+        assert_eq!(timer.state(), TimerState::NotRunning);
+        timer.start();
+        assert_eq!(timer.state(), TimerState::Running);
+        timer.start();
+        assert_eq!(timer.timer.read().unwrap().current_split_index(), Some(0));
+    }
+
+    #[test]
+    fn splits_until_the_end_then_resets() {
+        let mut timer = auto_splitter_timer();
+        timer.start();
+        timer.split();
+        timer.split();
+        assert_eq!(timer.state(), TimerState::Ended);
+        timer.split();
+        timer.reset();
+        assert_eq!(timer.state(), TimerState::NotRunning);
+        assert_eq!(timer.timer.read().unwrap().run().attempt_count(), 1);
+    }
+
+    #[test]
+    fn game_time_is_set() {
+        let mut timer = auto_splitter_timer();
+        timer.start();
+        timer.pause_game_time();
+        timer.set_game_time(time::Duration::seconds(42));
+        let game_time = timer
+            .timer
+            .read()
+            .unwrap()
+            .snapshot()
+            .current_time()
+            .game_time;
+        assert_eq!(game_time, Some(TimeSpan::from_seconds(42.0)));
+    }
+
+    #[test]
+    fn missing_auto_splitter_is_unloaded() {
+        let AutoSplitterTimer { timer, splits } = auto_splitter_timer();
+        start_auto_splitter(PathBuf::from("missing.wasm"), timer, splits.clone());
+        let started = Instant::now();
+        loop {
+            let status = splits
+                .read()
+                .unwrap()
+                .get_auto_splitter_status()
+                .map(str::to_string);
+            if let Some(status) = status.filter(|s| s != "loading") {
+                assert!(status.starts_with("unloaded"), "{status}");
+                break;
+            }
+            assert!(
+                started.elapsed() < UPDATE_TIMEOUT,
+                "auto splitter still loading"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Save the run file in the background, so a crash or a killed process does
+//! not lose attempts of the session
+//!
+//! Saved run data (attempt history, best segments, personal best) only changes
+//! when an attempt starts or is reset. The run is checked at a fixed interval
+//! and saved only if it changed, which debounces quick successive resets
+use crate::persistence::{save_run_to_file, SpeedrunSettings};
+use crate::timer_controls::TimerControlError;
+use crate::ui::Splits;
+use livesplit_core::Timer;
+use log::{error, info};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// Save run of `timer` to the run file of `settings` every `interval` if it
+/// changed. Nothing is saved in practice mode
+///
+/// # Panics
+/// Panics if timer or splits lock cannot be acquired
+pub fn start_autosave(
+    timer: Arc<RwLock<Timer>>,
+    splits: Arc<RwLock<Splits>>,
+    settings: SpeedrunSettings,
+    interval: Duration,
+) {
+    info!("Autosaving run every {}s", interval.as_secs_f32());
+    thread::spawn(move || {
+        // run was saved at startup
+        let mut last_saved = match timer.read().map_err(TimerControlError::TimerReadLock) {
+            Ok(timer) => run_version(&timer),
+            Err(e) => {
+                error!("{e}");
+                panic!("{e}") // cannot recover
+            }
+        };
+        loop {
+            thread::sleep(interval);
+            let timer = match timer.read().map_err(TimerControlError::TimerReadLock) {
+                Ok(timer) => timer,
+                Err(e) => {
+                    error!("{e}");
+                    panic!("{e}") // cannot recover
+                }
+            };
+            let splits = match splits.read().map_err(TimerControlError::SplitsReadLock) {
+                Ok(splits) => splits,
+                Err(e) => {
+                    error!("{e}");
+                    panic!("{e}") // cannot recover
+                }
+            };
+            let version = run_version(&timer);
+            if splits.is_practicing() || version == last_saved {
+                continue;
+            }
+            let run = splits.get_saved_run(&timer);
+            // do not block the timer while writing
+            drop(splits);
+            drop(timer);
+            match save_run_to_file(&run, &settings) {
+                Ok(()) => {
+                    info!("Autosaved run");
+                    last_saved = version;
+                }
+                Err(e) => error!("{e}"),
+            }
+        }
+    });
+}
+
+/// Returns attempt count and number of recorded attempts of run of `timer`,
+/// which change every time the run file content changes
+pub(crate) fn run_version(timer: &Timer) -> (u32, usize) {
+    (
+        timer.run().attempt_count(),
+        timer.run().attempt_history().len(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::timer;
+    use livesplit_core::TimingMethod;
+
+    #[test]
+    fn run_version_changes_when_attempts_start_and_reset() {
+        let mut timer = timer(&["End"], TimingMethod::RealTime);
+        let loaded = run_version(&timer);
+
+        timer.start();
+        let started = run_version(&timer);
+        assert_ne!(started, loaded);
+        timer.split();
+        assert_eq!(run_version(&timer), started);
+
+        timer.reset(true);
+        assert_ne!(run_version(&timer), started);
+    }
+}
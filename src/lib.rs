@@ -4,8 +4,19 @@
 #![deny(rustdoc::broken_intra_doc_links)]
 #![doc = include_str!("../README.md")]
 #![warn(clippy::pedantic)]
+#[cfg(feature = "auto-splitting")]
+pub mod auto_splitter;
+pub mod autosave;
 pub mod persistence;
+pub mod recovery;
+pub mod segment_practice;
+pub mod server;
+pub mod state_feed;
+pub mod stats;
+pub mod text_outputs;
+pub mod time_format;
 pub mod timer_controls;
+pub mod tui;
 pub mod ui;
 
 use livesplit_core::hotkey::KeyCode;
@@ -19,17 +30,104 @@ pub struct Keybinding {
     pause_key: KeyCode,
     unpause_key: KeyCode,
     comparison_key: KeyCode,
+    #[serde(default)]
+    undo_split_key: Option<KeyCode>,
+    #[serde(default)]
+    skip_split_key: Option<KeyCode>,
+    #[serde(default)]
+    timing_method_key: Option<KeyCode>,
+    #[serde(default)]
+    segment_times_key: Option<KeyCode>,
+    #[serde(default)]
+    practice_key: Option<KeyCode>,
+    #[serde(default)]
+    game_time_pause_key: Option<KeyCode>,
+}
+
+/// Summary rows displayed below the splits. Missing rows are shown
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct SummaryRows {
+    sum_of_best: bool,
+    best_possible_time: bool,
+    possible_time_save: bool,
+    previous_segment: bool,
+}
+
+impl Default for SummaryRows {
+    fn default() -> Self {
+        SummaryRows {
+            sum_of_best: true,
+            best_possible_time: true,
+            possible_time_save: true,
+            previous_segment: true,
+        }
+    }
+}
+
+/// Colors of time differences ("#RRGGBB"). Missing colors use livesplit
+/// defaults
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DeltaColors {
+    gold: String,
+    ahead_gaining: String,
+    ahead_losing: String,
+    behind_gaining: String,
+    behind_losing: String,
+}
+
+impl Default for DeltaColors {
+    fn default() -> Self {
+        DeltaColors {
+            gold: "#D8AF1F".to_string(),
+            ahead_gaining: "#00CC36".to_string(),
+            ahead_losing: "#52CC73".to_string(),
+            behind_gaining: "#CC5C52".to_string(),
+            behind_losing: "#CC1200".to_string(),
+        }
+    }
+}
+
+/// Confirmations asked when resetting an attempt
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(default)]
+pub struct ResetPolicy {
+    /// reset key has to be pressed twice to reset
+    double_press: bool,
+    /// maximum delay between both presses of the reset key (milliseconds)
+    double_press_window_ms: u64,
+    /// ask whether improved best segments of an unfinished attempt are kept
+    ask_to_save_golds: bool,
+}
+
+impl Default for ResetPolicy {
+    fn default() -> Self {
+        ResetPolicy {
+            double_press: false,
+            double_press_window_ms: 1000,
+            ask_to_save_golds: true,
+        }
+    }
 }
 
 impl Keybinding {
     /// Return Keybinding for the application
     #[must_use]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         split_key: KeyCode,
         reset_key: KeyCode,
         pause_key: KeyCode,
         unpause_key: KeyCode,
         comparison_key: KeyCode,
+        undo_split_key: Option<KeyCode>,
+        skip_split_key: Option<KeyCode>,
+        timing_method_key: Option<KeyCode>,
+        segment_times_key: Option<KeyCode>,
+        practice_key: Option<KeyCode>,
+        game_time_pause_key: Option<KeyCode>,
     ) -> Keybinding {
         Keybinding {
             split_key,
@@ -37,6 +135,59 @@ impl Keybinding {
             pause_key,
             unpause_key,
             comparison_key,
+            undo_split_key,
+            skip_split_key,
+            timing_method_key,
+            segment_times_key,
+            practice_key,
+            game_time_pause_key,
+        }
+    }
+}
+
+/// Fixtures shared by the tests of every module
+#[cfg(test)]
+pub(crate) mod test_support {
+    use crate::time_format::TimeFormat;
+    use crate::ui::Splits;
+    use livesplit_core::{Run, Segment, TimeSpan, Timer, TimingMethod};
+    use std::sync::{Arc, RwLock};
+
+    /// Returns timer of a run with segments `names` using `timing_method`
+    pub(crate) fn timer(names: &[&str], timing_method: TimingMethod) -> Timer {
+        let mut run = Run::new();
+        for name in names {
+            run.push_segment(Segment::new(*name));
+        }
+        let mut timer = Timer::new(run).expect("run has segments");
+        timer.set_current_timing_method(timing_method);
+        timer
+    }
+
+    /// Returns timer of a run with segments `names` using `timing_method` and
+    /// its splits display
+    pub(crate) fn timer_and_splits(
+        names: &[&str],
+        timing_method: TimingMethod,
+    ) -> (Arc<RwLock<Timer>>, Arc<RwLock<Splits>>) {
+        let splits = Splits::new(
+            names.iter().map(ToString::to_string).collect(),
+            TimeFormat::default(),
+        );
+        (
+            Arc::new(RwLock::new(timer(names, timing_method))),
+            Arc::new(RwLock::new(splits)),
+        )
+    }
+
+    /// Start an attempt of `timer` and split at game times `split_times`
+    pub(crate) fn split_at(timer: &mut Timer, split_times: &[f64]) {
+        timer.start();
+        timer.initialize_game_time();
+        timer.pause_game_time();
+        for time in split_times {
+            timer.set_game_time(TimeSpan::from_seconds(*time));
+            timer.split();
         }
     }
 }
@@ -1,13 +1,33 @@
-use clap::{crate_authors, crate_name, crate_version, Arg, Command};
+use clap::{crate_authors, crate_name, crate_version, Arg, ArgMatches, Command};
 use dialog::DialogBox;
+use itertools::Itertools;
 use livesplit_core::hotkey::KeyCode;
-use livesplit_core::{Run, Segment, TimeSpan, Timer};
+use livesplit_core::{Run, Segment, TimeSpan, Timer, TimingMethod};
 use log::*;
-use pmls::timer_controls::{pause, reset, start_or_split_timer, switch_comparison, unpause};
-use pmls::ui::{SpeedrunDisplay, Splits};
+#[cfg(feature = "auto-splitting")]
+use pmls::auto_splitter::start_auto_splitter;
+use pmls::autosave::start_autosave;
+use pmls::recovery::{
+    read_journal, remove_journal, set_journal_aside, start_journal, Journal, Recovery,
+    RecoveryError,
+};
+use pmls::segment_practice::{find_split, SegmentPractice};
+use pmls::server::start_livesplit_server;
+use pmls::state_feed::start_state_feed;
+use pmls::stats::RunStatistics;
+use pmls::text_outputs::{start_text_outputs, TextOutput};
+use pmls::timer_controls::{
+    pause, reset, skip_split, start_or_split_timer, switch_comparison, toggle_game_time_pause,
+    toggle_practice, toggle_segment_times, toggle_timing_method, undo_split, unpause,
+};
+use pmls::tui::run_tui;
+#[cfg(feature = "gui")]
+use pmls::ui::SpeedrunDisplay;
+use pmls::ui::Splits;
 use pmls::{persistence::*, Keybinding as lKeybinding};
 use simplelog::{Config, WriteLogger};
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::sync::{Arc, RwLock};
 
@@ -29,8 +49,9 @@ device temporarily to the current $USER.
 
 When executed as $USER (find the value with `echo $USER`), files will be \
 placed under:
-* /home/<USER>/.{appname}
-* /home/<USER>/.config/.{appname}
+* $XDG_DATA_HOME/{appname} (default: /home/<USER>/.local/share/{appname})
+* $XDG_CONFIG_HOME/{appname} (default: /home/<USER>/.config/{appname})
+* $XDG_STATE_HOME/{appname} (default: /home/<USER>/.local/state/{appname})
 
 Note: when run with sudo, replaces /home/<USER> with /root
 
@@ -38,106 +59,168 @@ Note2: if you are willing to introduce a vulnerability permanently, add $USER \
 to \"input\" group (group owner of eventXXX (`ls -la /dev/input/`))
 "
     );
+    let speedrun_game_arg = Arg::new("game")
+        .required(true)
+        .help("The game name of the speedrun")
+        .value_name("GAME");
+    let speedrun_category_arg = Arg::new("category")
+        .required(true)
+        .help("The game category name of the speedrun")
+        .value_name("CATEGORY");
+    // speedrun arguments that can be changed after creation
+    let editable_args = [
+        "split-key",
+        "reset-key",
+        "pause-key",
+        "unpause-key",
+        "comparison-key",
+        "undo-split-key",
+        "skip-split-key",
+        "timing-method-key",
+        "segment-times-key",
+        "practice-key",
+        "game-time-pause-key",
+        "timing-method",
+        "layout",
+        "auto-splitter",
+    ];
     let cmd = Command::new(crate_name!())
         .author(crate_authors!())
         .version(crate_version!())
         .about("Poor man's splitlive for linux")
+        // NOTE: running speedrun without subcommand is kept for compatibility
+        .args(speedrun_args(accept_help_msg.as_str()))
         .arg(
-            Arg::new("game")
-                .requires("category")
-                .long("game")
-                .long_help("The game name when loading speedrun (\"GAME_CATEGORY\" is the file name searched in data folder)
-When used with --force-speedrun-settings-creation, provides the name of the game.")
+            Arg::new("config")
+                .long("config")
+                .global(true)
                 .takes_value(true)
-                .value_name("GAME"),
+                .value_name("PATH")
+                .help("Use configuration file at PATH instead of the default one"),
         )
         .arg(
-            Arg::new("category")
-                .requires("game")
-                .long("category")
-                .long_help("The game category name when loading speedrun (\"GAME_CATEGORY\" is the file name searched in data folder)
-When used with --force-speedrun-settings-creation, provides the category name of the game.")
+            Arg::new("data-dir")
+                .long("data-dir")
+                .global(true)
                 .takes_value(true)
-                .value_name("CATEGORY"),
+                .value_name("DIR")
+                .help("Store speedrun files in DIR instead of the configured data folder"),
         )
-        .arg(
-            Arg::new("split-names")
-                .short('n')
-                .long("split-names")
-                .help("The split names when creating a speedrun")
-                .takes_value(true)
-                .value_name("CATEGORY"),
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("run")
+                .about("Run speedrun (default when no subcommand is given)")
+                .args(speedrun_args(accept_help_msg.as_str()))
+                .after_help(after_help_msg.as_str()),
+        )
+        .subcommand(Command::new("list").about("List speedruns of data folder"))
+        .subcommand(
+            Command::new("import")
+                .about("Create speedrun from splits file of another timer")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .help("Splits file (livesplit, wsplit, llanfair, splits.io...)")
+                        .value_name("FILE"),
+                )
+                .arg(
+                    Arg::new("game")
+                        .long("game")
+                        .takes_value(true)
+                        .value_name("GAME")
+                        .help("Replace game name of splits file"),
+                )
+                .arg(
+                    Arg::new("category")
+                        .long("category")
+                        .takes_value(true)
+                        .value_name("CATEGORY")
+                        .help("Replace category name of splits file"),
+                )
+                .args(
+                    speedrun_args(accept_help_msg.as_str())
+                        .into_iter()
+                        .filter(|a| {
+                            a.get_id() == "make-speedrun-default"
+                                || (editable_args.contains(&a.get_id())
+                                    && !["layout", "auto-splitter"].contains(&a.get_id()))
+                        }),
+                ),
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Show settings and run summary of speedrun")
+                .arg(speedrun_game_arg.clone())
+                .arg(speedrun_category_arg.clone()),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Show statistics of past attempts of speedrun")
+                .arg(speedrun_game_arg.clone())
+                .arg(speedrun_category_arg.clone()),
+        )
+        .subcommand(
+            Command::new("edit")
+                .about("Change keybindings, timing method, layout, auto splitter or split icons of speedrun")
+                .arg(speedrun_game_arg.clone())
+                .arg(speedrun_category_arg.clone())
+                .args(
+                    speedrun_args(accept_help_msg.as_str())
+                        .into_iter()
+                        .filter(|a| editable_args.contains(&a.get_id())),
+                )
+                .arg(
+                    Arg::new("split-icon")
+                        .long("split-icon")
+                        .number_of_values(2)
+                        .multiple_occurrences(true)
+                        .value_names(&["SPLIT", "FILE"])
+                        .help(
+                            "Replace icon of split (name or number starting at 1) with image FILE",
+                        ),
+                )
+                .arg(
+                    Arg::new("remove-split-icon")
+                        .long("remove-split-icon")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .value_name("SPLIT")
+                        .help("Remove icon of split (name or number starting at 1)"),
+                ),
+        )
+        .subcommand(
+            Command::new("delete")
+                .about("Delete settings and run files of speedrun")
+                .arg(speedrun_game_arg.clone())
+                .arg(speedrun_category_arg.clone()),
+        )
+        .subcommand(
+            Command::new("restore")
+                .about("Replace run file of speedrun with one of its backups")
+                .arg(speedrun_game_arg.clone())
+                .arg(speedrun_category_arg.clone())
+                .arg(
+                    Arg::new("backup")
+                        .help("Timestamp of restored backup (most recent backup when missing)")
+                        .value_name("TIMESTAMP"),
+                )
+                .arg(
+                    Arg::new("list")
+                        .long("list")
+                        .help("List timestamps of backups instead of restoring one"),
+                ),
+        )
+        .subcommand(
+            Command::new("set-default")
+                .about("Open speedrun when launching application with no arguments")
+                .arg(speedrun_game_arg)
+                .arg(speedrun_category_arg),
         )
-        .arg(
-            Arg::new("split-key")
-                .short('s')
-                .long("split-key")
-                .help("Assign split key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
-                .takes_value(true)
-                .value_name("SPLIT KEY"),
-            )
-        .arg(
-            Arg::new("reset-key")
-                .short('r')
-                .long("reset-key")
-                .help("Assign reset key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
-                .takes_value(true)
-                .value_name("RESET KEY"),
-            )
-        .arg(
-            Arg::new("pause-key")
-                .short('p')
-                .long("pause-key")
-                .help("Assign pause key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
-                .takes_value(true)
-                .value_name("PAUSE KEY"),
-            )
-        .arg(
-            // NOTE: not named resume because short argument conflicts with reset
-            Arg::new("unpause-key")
-                .short('u')
-                .long("unpause-key")
-                .help("Assign unpause key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
-                .takes_value(true)
-                .value_name("UNPAUSE KEY"),
-            )
-        .arg(
-            Arg::new("comparison-key")
-                .short('c')
-                .long("comparison-key")
-                .help("Assign comparison key to switch between standard comparisons (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
-                .takes_value(true)
-                .value_name("COMPARISON KEY"),
-            )
-        .arg(
-            Arg::new("icons")
-                .short('i')
-                .long("icons")
-                .help("Give icon filepath for speedrun creation")
-                .takes_value(true)
-                .multiple_values(true)
-                .value_name("ICON FILEPATH"),
-            )
-        .arg(
-            Arg::new("accept-automatically-configuration-creation")
-                .long("accept-automatically-configuration-creation")
-                .help(accept_help_msg.as_str())
-            )
-        .arg(
-            Arg::new(force_speedrun_settings_creation_arg_name)
-                .long(force_speedrun_settings_creation_arg_name)
-                .help("Avoid behavior where program defaults to finding speedrun by name.")
-            )
-        .arg(
-            Arg::new("make-speedrun-default")
-                .long("make-speedrun-default")
-                .help("Make created speedrun default")
-            )
         .after_help(after_help_msg.as_str());
     let m = cmd.clone().get_matches();
 
-    // create default data directory
-    let default_data_folder = match default_data_folder() {
+    // don't log until --help is parsed
+    let default_log_file_path = match default_log_file_path() {
         Ok(f) => f,
         Err(e) => {
             println!("{e}");
@@ -145,19 +228,14 @@ When used with --force-speedrun-settings-creation, provides the category name of
             return std::process::ExitCode::FAILURE;
         }
     };
-    if !std::path::Path::new(default_data_folder.as_str()).exists() {
-        fs::create_dir(default_data_folder).unwrap();
-    }
-
-    // don't log until --help is parsed
-    let default_log_file_path = match default_log_file_path() {
-        Ok(f) => f,
-        Err(e) => {
+    // create log directory
+    if let Some(log_folder) = default_log_file_path.parent() {
+        if let Err(e) = fs::create_dir_all(log_folder) {
             println!("{e}");
             exit_error_message(appname);
             return std::process::ExitCode::FAILURE;
         }
-    };
+    }
 
     let f = match fs::OpenOptions::new()
         .append(true)
@@ -181,6 +259,36 @@ When used with --force-speedrun-settings-creation, provides the category name of
         }
     }
 
+    let data_folder = m.value_of("data-dir").map(Path::new);
+    let config_path = match m.value_of("config") {
+        Some(config_path) => PathBuf::from(config_path),
+        None => {
+            // files of previous versions are only moved to default locations
+            if let Err(e) = migrate_legacy_files() {
+                error!("{e}");
+                println!("{e}");
+                exit_error_message(appname);
+                return std::process::ExitCode::FAILURE;
+            }
+            match default_config_path() {
+                Ok(config_path) => config_path,
+                Err(e) => {
+                    error!("{e}");
+                    exit_error_message(appname);
+                    return std::process::ExitCode::FAILURE;
+                }
+            }
+        }
+    };
+
+    let m = match m.subcommand() {
+        Some(("run", run_matches)) => run_matches.clone(),
+        Some((subcommand, subcommand_matches)) => {
+            return manage_speedrun(subcommand, subcommand_matches, &config_path, data_folder)
+        }
+        None => m.clone(),
+    };
+
     // user arguments to load specific speedrun
     let game = m.value_of("game");
     let category = m.value_of("category");
@@ -190,13 +298,40 @@ When used with --force-speedrun-settings-creation, provides the category name of
     let pause_key = m.value_of("pause-key");
     let unpause_key = m.value_of("unpause-key");
     let comparison_key = m.value_of("comparison-key");
+    let undo_split_key = m.value_of("undo-split-key");
+    let skip_split_key = m.value_of("skip-split-key");
+    let timing_method_key = m.value_of("timing-method-key");
+    let segment_times_key = m.value_of("segment-times-key");
+    let practice_key = m.value_of("practice-key");
+    let game_time_pause_key = m.value_of("game-time-pause-key");
+    let timing_method = m.value_of("timing-method");
     let icons = m.values_of("icons");
+    let layout = m.value_of("layout");
+    let auto_splitter = m.value_of("auto-splitter");
+    let server_port = m.value_of_t::<u16>("server-port").ok();
+    let state_feed_port = m.value_of_t::<u16>("state-feed-port").ok();
+    let text_outputs = m.values_of("text-outputs").map(|outputs| {
+        outputs
+            .filter_map(TextOutput::from_name)
+            .collect::<Vec<_>>()
+    });
+    #[cfg(feature = "gui")]
+    let tui = m.is_present("tui");
     let accept_pmls_configuration_creating_dialog =
         m.is_present("accept-automatically-configuration-creation");
     let force_ss_creation = m.is_present("force-speedrun-settings-creation");
     let make_speedrun_default = m.is_present("make-speedrun-default");
+    let practice_from = m.value_of("practice-from");
+    let practice_to = m.value_of("practice-to");
+    // segment practice always runs in practice mode
+    let practice = m.is_present("practice") || practice_from.is_some();
+    let recover_attempt = m.value_of("recover-attempt");
 
-    let config = match parse_configuration(accept_pmls_configuration_creating_dialog) {
+    let config = match parse_configuration(
+        accept_pmls_configuration_creating_dialog,
+        &config_path,
+        data_folder,
+    ) {
         Ok(c) => c,
         Err(e) => match e {
             ConfigurationFileError::UserCancel() => {
@@ -219,13 +354,25 @@ When used with --force-speedrun-settings-creation, provides the category name of
             }
         },
     };
-    let keybinding =
-        UserKeybinding::new(split_key, reset_key, pause_key, unpause_key, comparison_key);
-    let (settings, image_names, is_new) = match load_speedrun_settings(
+    let keybinding = UserKeybinding::new(
+        split_key,
+        reset_key,
+        pause_key,
+        unpause_key,
+        comparison_key,
+        undo_split_key,
+        skip_split_key,
+        timing_method_key,
+        segment_times_key,
+        practice_key,
+        game_time_pause_key,
+    );
+    let (mut settings, image_names, is_new) = match load_speedrun_settings(
         &config,
         game,
         category,
         split_names,
+        timing_method,
         keybinding,
         icons,
         force_ss_creation,
@@ -256,9 +403,34 @@ When used with --force-speedrun-settings-creation, provides the category name of
     };
 
     if is_new {
-        if let Err(e) =
-            update_configuration_with_default_speedrun(config, &settings, make_speedrun_default)
-        {
+        if let Err(e) = update_configuration_with_default_speedrun(
+            config.clone(),
+            &settings,
+            make_speedrun_default,
+        ) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+    if let Some(layout) = layout {
+        if let Err(e) = settings.set_layout_path(layout) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+    if let Some(auto_splitter) = auto_splitter {
+        if let Err(e) = settings.set_auto_splitter_path(auto_splitter) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    // copy icons selected at creation into the speedrun folder
+    for (i, path) in image_names.unwrap_or_default().iter().enumerate() {
+        if let Err(e) = import_split_icon(&mut settings, i, Path::new(path)) {
             error!("{e}");
             exit_error_message(appname);
             return std::process::ExitCode::FAILURE;
@@ -270,34 +442,25 @@ When used with --force-speedrun-settings-creation, provides the category name of
         return std::process::ExitCode::FAILURE;
     }
 
-    let splits: Arc<RwLock<Splits>> =
-        Arc::new(RwLock::new(Splits::new(settings.get_split_names())));
+    let mut splits = Splits::new(settings.get_split_names(), settings.get_time_format());
+    splits.set_reset_policy(settings.get_reset_policy());
+    let splits: Arc<RwLock<Splits>> = Arc::new(RwLock::new(splits));
 
     // split display references for callback functions
     let start_cb_splits_ref: Arc<RwLock<Splits>> = splits.clone();
     let reset_cb_splits_ref: Arc<RwLock<Splits>> = splits.clone();
     let comparison_cb_splits_ref: Arc<RwLock<Splits>> = splits.clone();
+    let undo_split_cb_splits_ref: Arc<RwLock<Splits>> = splits.clone();
+    let skip_split_cb_splits_ref: Arc<RwLock<Splits>> = splits.clone();
+    let timing_method_cb_splits_ref: Arc<RwLock<Splits>> = splits.clone();
+    let segment_times_cb_splits_ref: Arc<RwLock<Splits>> = splits.clone();
+    let practice_cb_splits_ref: Arc<RwLock<Splits>> = splits.clone();
 
     let mut run = Run::new();
-    run.set_game_name(&settings.get_game_name());
-    run.set_category_name(&settings.get_category_name());
-    for (i, name) in settings.get_split_names().iter().enumerate() {
-        let mut s = Segment::new(name);
-        if let Some(names) = image_names.clone() {
-            if let Some(path) = names.get(i) {
-                let img = vec![];
-                let image = match livesplit_core::settings::Image::from_file(path, img) {
-                    Ok(i) => i,
-                    Err(e) => {
-                        error!("{e}");
-                        exit_error_message(appname);
-                        return std::process::ExitCode::FAILURE;
-                    }
-                };
-                s.set_icon(image);
-            }
-        }
-        run.push_segment(s);
+    run.set_game_name(settings.get_game_name());
+    run.set_category_name(settings.get_category_name());
+    for name in settings.get_split_names() {
+        run.push_segment(Segment::new(name));
     }
 
     // save run and initialize current comparison
@@ -310,16 +473,77 @@ When used with --force-speedrun-settings-creation, provides the category name of
             warn!("Could not parse run file: {e}");
         }
     };
+    load_split_icons(&settings, &mut run);
 
-    if let Err(e) = save_run_to_file(&run, &settings) {
+    let journal_path = journal_path(&settings);
+    // NOTE: interrupted attempt is kept for next launch outside practice mode
+    let resumed_attempt = if practice {
+        None
+    } else {
+        match recover_interrupted_attempt(&journal_path, &mut run, &settings, recover_attempt) {
+            Ok(journal) => journal,
+            Err(e) => {
+                error!("{e}");
+                exit_error_message(appname);
+                return std::process::ExitCode::FAILURE;
+            }
+        }
+    };
+
+    if practice {
+        info!("Practice mode: run file is not written");
+        let mut segment_practice = None;
+        if let Some(from) = practice_from {
+            let log_path = practice_log_path(&settings);
+            match SegmentPractice::new(&settings.get_split_names(), from, practice_to, log_path) {
+                Ok(practice) => segment_practice = Some(practice),
+                Err(e) => {
+                    error!("{e}");
+                    exit_error_message(appname);
+                    return std::process::ExitCode::FAILURE;
+                }
+            }
+        }
+        match splits.write() {
+            Ok(mut splits) => {
+                splits.set_practice(true);
+                splits.set_segment_practice(segment_practice);
+            }
+            Err(e) => {
+                error!("{e}");
+                exit_error_message(appname);
+                return std::process::ExitCode::FAILURE;
+            }
+        }
+    } else if let Err(e) = save_run_to_file(&run, &settings) {
         error!("{e}");
         exit_error_message(appname);
         return std::process::ExitCode::FAILURE;
+    } else if resumed_attempt.is_none() {
+        // recovered attempt is recorded in the saved run file
+        if let Err(e) = remove_journal(&journal_path) {
+            error!("\"{}\": {e}", journal_path.display());
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
     }
 
     // Arc allows any thread to point to some variable but it does not allow to
     // mutate it. This is why is wrapping a RwLock
-    let t = Arc::new(RwLock::new(Timer::new(run.clone()).expect("")));
+    let mut timer = Timer::new(run.clone()).expect("");
+    timer.set_current_timing_method(settings.get_timing_method());
+    if let Some(journal) = resumed_attempt {
+        let run_offset = journal.resume(&mut timer);
+        match splits.write() {
+            Ok(mut splits) => splits.set_run_offset(run_offset),
+            Err(e) => {
+                error!("{e}");
+                exit_error_message(appname);
+                return std::process::ExitCode::FAILURE;
+            }
+        }
+    }
+    let t = Arc::new(RwLock::new(timer));
 
     // load current comparison into the UI
     match t.read() {
@@ -327,10 +551,11 @@ When used with --force-speedrun-settings-creation, provides the category name of
             let mut splits = splits.write().unwrap();
             for (i, s) in run.segments().iter().enumerate() {
                 let comparison = s.comparison(timer.current_comparison());
-                if let Some(loaded_comparison) = comparison.real_time {
+                if let Some(loaded_comparison) = comparison[timer.current_timing_method()] {
                     splits.update_split(i, TimeSpan::zero(), loaded_comparison);
                 }
             }
+            splits.update_analysis(&timer);
         }
         Err(e) => {
             error!("{e}");
@@ -345,6 +570,11 @@ When used with --force-speedrun-settings-creation, provides the category name of
     let pause_cb_timer_ref = t.clone();
     let unpause_cb_timer_ref = t.clone();
     let comparison_cb_timer_ref = t.clone();
+    let undo_split_cb_timer_ref = t.clone();
+    let skip_split_cb_timer_ref = t.clone();
+    let timing_method_cb_timer_ref = t.clone();
+    let practice_cb_timer_ref = t.clone();
+    let game_time_pause_cb_timer_ref = t.clone();
 
     debug!("{:?}", KeyCode::Numpad1);
     debug!("{:#?}", KeyCode::Numpad1);
@@ -353,7 +583,7 @@ When used with --force-speedrun-settings-creation, provides the category name of
     info!("split key: {split_key:?}");
 
     let hook = livesplit_core::hotkey::Hook::new().unwrap();
-    if let Err(e) = hook.register(split_key, move || {
+    if let Err(e) = hook.register(split_key.into(), move || {
         start_or_split_timer(&start_cb_timer_ref, &start_cb_splits_ref)
     }) {
         error!("{e}");
@@ -363,7 +593,7 @@ When used with --force-speedrun-settings-creation, provides the category name of
 
     let reset_key = settings.get_reset_key();
     info!("reset key: {reset_key:?}");
-    if let Err(e) = hook.register(reset_key, move || {
+    if let Err(e) = hook.register(reset_key.into(), move || {
         reset(&reset_cb_timer_ref, &reset_cb_splits_ref)
     }) {
         error!("{e}");
@@ -373,7 +603,7 @@ When used with --force-speedrun-settings-creation, provides the category name of
 
     let pause_key = settings.get_pause_key();
     info!("pause key: {pause_key:?}");
-    if let Err(e) = hook.register(pause_key, move || pause(&pause_cb_timer_ref)) {
+    if let Err(e) = hook.register(pause_key.into(), move || pause(&pause_cb_timer_ref)) {
         error!("{e}");
         exit_error_message(appname);
         return std::process::ExitCode::FAILURE;
@@ -381,7 +611,7 @@ When used with --force-speedrun-settings-creation, provides the category name of
 
     let unpause_key = settings.get_unpause_key();
     info!("unpause key: {unpause_key:?}");
-    if let Err(e) = hook.register(unpause_key, move || unpause(&unpause_cb_timer_ref)) {
+    if let Err(e) = hook.register(unpause_key.into(), move || unpause(&unpause_cb_timer_ref)) {
         error!("{e}");
         exit_error_message(appname);
         return std::process::ExitCode::FAILURE;
@@ -389,7 +619,7 @@ When used with --force-speedrun-settings-creation, provides the category name of
 
     let comparison_key = settings.get_comparison_key();
     info!("comparison key: {comparison_key:?}");
-    if let Err(e) = hook.register(comparison_key, move || {
+    if let Err(e) = hook.register(comparison_key.into(), move || {
         switch_comparison(&comparison_cb_timer_ref, &comparison_cb_splits_ref)
     }) {
         error!("{e}");
@@ -397,19 +627,196 @@ When used with --force-speedrun-settings-creation, provides the category name of
         return std::process::ExitCode::FAILURE;
     }
 
+    let undo_split_key = settings.get_undo_split_key();
+    info!("undo split key: {undo_split_key:?}");
+    if let Some(undo_split_key) = undo_split_key {
+        if let Err(e) = hook.register(undo_split_key.into(), move || {
+            undo_split(&undo_split_cb_timer_ref, &undo_split_cb_splits_ref)
+        }) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    let skip_split_key = settings.get_skip_split_key();
+    info!("skip split key: {skip_split_key:?}");
+    if let Some(skip_split_key) = skip_split_key {
+        if let Err(e) = hook.register(skip_split_key.into(), move || {
+            skip_split(&skip_split_cb_timer_ref, &skip_split_cb_splits_ref)
+        }) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    let timing_method_key = settings.get_timing_method_key();
+    info!("timing method key: {timing_method_key:?}");
+    if let Some(timing_method_key) = timing_method_key {
+        if let Err(e) = hook.register(timing_method_key.into(), move || {
+            toggle_timing_method(&timing_method_cb_timer_ref, &timing_method_cb_splits_ref)
+        }) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    let segment_times_key = settings.get_segment_times_key();
+    info!("segment times key: {segment_times_key:?}");
+    if let Some(segment_times_key) = segment_times_key {
+        if let Err(e) = hook.register(segment_times_key.into(), move || {
+            toggle_segment_times(&segment_times_cb_splits_ref)
+        }) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    let practice_key = settings.get_practice_key();
+    info!("practice key: {practice_key:?}");
+    if let Some(practice_key) = practice_key {
+        let practice_cb_settings = settings.clone();
+        if let Err(e) = hook.register(practice_key.into(), move || {
+            toggle_practice(
+                &practice_cb_timer_ref,
+                &practice_cb_splits_ref,
+                &practice_cb_settings,
+            );
+        }) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    let game_time_pause_key = settings.get_game_time_pause_key();
+    info!("game time pause key: {game_time_pause_key:?}");
+    if let Some(game_time_pause_key) = game_time_pause_key {
+        if let Err(e) = hook.register(game_time_pause_key.into(), move || {
+            toggle_game_time_pause(&game_time_pause_cb_timer_ref);
+        }) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    if let Some(auto_splitter) = settings.get_auto_splitter_path() {
+        #[cfg(feature = "auto-splitting")]
+        start_auto_splitter(PathBuf::from(auto_splitter), t.clone(), splits.clone());
+        #[cfg(not(feature = "auto-splitting"))]
+        warn!("pmls is built without auto splitting, \"{auto_splitter}\" is not loaded");
+    }
+
+    let layout = match parse_layout_from_file(&settings) {
+        Ok(layout) => layout,
+        Err(e) => {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+
+    if config.is_livesplit_server_enabled() || server_port.is_some() {
+        let port = server_port.unwrap_or_else(|| config.get_livesplit_server_port());
+        if let Err(e) =
+            start_livesplit_server(port, t.clone(), splits.clone(), settings.get_time_format())
+        {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    if config.is_state_feed_enabled() || state_feed_port.is_some() {
+        let port = state_feed_port.unwrap_or_else(|| config.get_state_feed_port());
+        if let Err(e) =
+            start_state_feed(port, t.clone(), splits.clone(), settings.get_time_format())
+        {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    let text_outputs = text_outputs.unwrap_or_else(|| config.get_text_outputs());
+    if !text_outputs.is_empty() {
+        if let Err(e) = start_text_outputs(
+            config.get_text_outputs_folder(),
+            text_outputs,
+            t.clone(),
+            splits.clone(),
+            settings.get_time_format(),
+        ) {
+            error!("{e}");
+            exit_error_message(appname);
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+
+    start_journal(t.clone(), splits.clone(), journal_path);
+
+    if let Some(interval) = config.get_autosave_interval() {
+        start_autosave(t.clone(), splits.clone(), settings.clone(), interval);
+    }
+
+    let keybinding = lKeybinding::new(
+        split_key,
+        reset_key,
+        pause_key,
+        unpause_key,
+        comparison_key,
+        undo_split_key,
+        skip_split_key,
+        timing_method_key,
+        segment_times_key,
+        practice_key,
+        game_time_pause_key,
+    );
+
+    #[cfg(feature = "gui")]
+    if !tui {
+        show_window(appname, t, splits, keybinding, settings, layout);
+    }
+
+    if layout.is_some() {
+        warn!("Layout is not displayed in the terminal");
+    }
+    // also blocking
+    if let Err(e) = run_tui(&t, &splits, keybinding, &settings) {
+        error!("{e}");
+        exit_error_message(appname);
+        return std::process::ExitCode::FAILURE;
+    }
+    std::process::ExitCode::SUCCESS
+}
+
+/// Display speedrun in a window until it is closed
+#[cfg(feature = "gui")]
+fn show_window(
+    appname: &str,
+    t: Arc<RwLock<Timer>>,
+    splits: Arc<RwLock<Splits>>,
+    keybinding: lKeybinding,
+    settings: SpeedrunSettings,
+    layout: Option<livesplit_core::Layout>,
+) -> ! {
     let options = eframe::NativeOptions::default();
-    let keybinding = lKeybinding::new(split_key, reset_key, pause_key, unpause_key, comparison_key);
     let mut app = SpeedrunDisplay::new(
         "Poor man's LiveSplit".to_owned(),
         t,
         splits,
         keybinding,
         settings,
+        layout,
     );
     if let Err(e) = app.init() {
         error!("{e}");
         exit_error_message(appname);
-        return std::process::ExitCode::FAILURE;
+        std::process::exit(1);
     }
 
     // also blocking
@@ -420,14 +827,531 @@ When used with --force-speedrun-settings-creation, provides the category name of
     );
 }
 
+/// Handle attempt interrupted by a crash, journaled at `journal_path`. The
+/// user chooses with `choice` (asked if missing) to resume it or to add it to
+/// the attempt history of `run` of speedrun `settings`. Returns journal of the
+/// attempt to resume. Otherwise the journal must be removed once `run` is
+/// saved
+fn recover_interrupted_attempt(
+    journal_path: &Path,
+    run: &mut Run,
+    settings: &SpeedrunSettings,
+    choice: Option<&str>,
+) -> Result<Option<Journal>, RecoveryError> {
+    let journal = match read_journal(journal_path) {
+        Ok(Some(journal)) => journal,
+        Ok(None) => return Ok(None),
+        Err(RecoveryError::Parse(e)) => {
+            let aside_path = set_journal_aside(journal_path)?;
+            warn!(
+                "Interrupted attempt is discarded, its journal is kept at \"{}\": {e}",
+                aside_path.display()
+            );
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+    let description = journal.describe(run, settings.get_time_format());
+    info!("{description}");
+    let choice = match choice {
+        Some(choice) => Recovery::from_name(choice)?,
+        // finished attempt only needs to be recorded
+        None if journal.is_finished() => Recovery::Reset,
+        None => match dialog::Question::new(format!(
+            "{description}. Resume it? Its completed splits are skipped and best segments and \
+personal best are not updated. Otherwise, it is added to the attempt history as a reset with \
+its split times."
+        ))
+        .title("Resume interrupted attempt?")
+        .show()
+        {
+            Ok(dialog::Choice::Yes) => Recovery::Resume,
+            Ok(_) => Recovery::Reset,
+            Err(e) => {
+                warn!("Could not ask to resume attempt: {e}");
+                Recovery::Reset
+            }
+        },
+    };
+    if choice == Recovery::Resume && !journal.is_finished() {
+        return Ok(Some(journal));
+    }
+    journal.reset(run, settings.get_timing_method());
+    Ok(None)
+}
+
+/// Execute speedrun management `subcommand` with its arguments `m`, using
+/// configuration file at `config_path` and speedrun files of `data_folder` if
+/// provided. No dialog is shown so it can be used from scripts
+fn manage_speedrun(
+    subcommand: &str,
+    m: &ArgMatches,
+    config_path: &Path,
+    data_folder: Option<&Path>,
+) -> ExitCode {
+    let config = match read_configuration(config_path, data_folder) {
+        Ok(c) => c,
+        Err(e) => return command_error(&e),
+    };
+    if subcommand == "list" {
+        let speedruns = match list_speedruns(&config) {
+            Ok(speedruns) => speedruns,
+            Err(e) => return command_error(&e),
+        };
+        for settings in speedruns {
+            println!(
+                "{}\t{}",
+                settings.get_game_name(),
+                settings.get_category_name()
+            );
+        }
+        return ExitCode::SUCCESS;
+    }
+    if subcommand == "import" {
+        return import_speedrun_file(config, m);
+    }
+
+    let game = m.value_of("game").unwrap_or_default();
+    let category = m.value_of("category").unwrap_or_default();
+    let settings = match find_speedrun(&config, game, category) {
+        Ok(settings) => settings,
+        Err(e) => return command_error(&e),
+    };
+    match subcommand {
+        "show" => show_speedrun(&settings),
+        "stats" => {
+            let run = match parse_run_from_file(&settings) {
+                Ok(run) => run,
+                Err(e) => return command_error(&e),
+            };
+            for line in RunStatistics::new(&run, settings.get_timing_method())
+                .report(settings.get_time_format())
+            {
+                println!("{line}");
+            }
+        }
+        "edit" => {
+            let keybinding = UserKeybinding::new(
+                m.value_of("split-key"),
+                m.value_of("reset-key"),
+                m.value_of("pause-key"),
+                m.value_of("unpause-key"),
+                m.value_of("comparison-key"),
+                m.value_of("undo-split-key"),
+                m.value_of("skip-split-key"),
+                m.value_of("timing-method-key"),
+                m.value_of("segment-times-key"),
+                m.value_of("practice-key"),
+                m.value_of("game-time-pause-key"),
+            );
+            let mut settings =
+                match edit_speedrun_settings(settings, &keybinding, m.value_of("timing-method")) {
+                    Ok(settings) => settings,
+                    Err(e) => return command_error(&e),
+                };
+            if let Some(layout) = m.value_of("layout") {
+                if let Err(e) = settings.set_layout_path(layout) {
+                    return command_error(&e);
+                }
+            }
+            if let Some(auto_splitter) = m.value_of("auto-splitter") {
+                if let Err(e) = settings.set_auto_splitter_path(auto_splitter) {
+                    return command_error(&e);
+                }
+            }
+            let split_names = settings.get_split_names();
+            let mut removed_icons = vec![];
+            for split in m.values_of("remove-split-icon").into_iter().flatten() {
+                let i = match find_split(&split_names, split) {
+                    Ok(i) => i,
+                    Err(e) => return command_error(&e),
+                };
+                if let Err(e) = remove_split_icon(&mut settings, i) {
+                    return command_error(&e);
+                }
+                removed_icons.push(i);
+            }
+            let split_icons = m.values_of("split-icon").into_iter().flatten().tuples();
+            let mut has_new_icons = false;
+            for (split, file) in split_icons {
+                let i = match find_split(&split_names, split) {
+                    Ok(i) => i,
+                    Err(e) => return command_error(&e),
+                };
+                if let Err(e) = import_split_icon(&mut settings, i, Path::new(file)) {
+                    return command_error(&e);
+                }
+                has_new_icons = true;
+            }
+            if let Err(e) = save_speedrun_settings_to_file(&settings) {
+                return command_error(&e);
+            }
+            // icons are also stored in the run file
+            if has_new_icons || !removed_icons.is_empty() {
+                match parse_run_from_file(&settings) {
+                    Ok(mut run) => {
+                        let segment_count = run.len();
+                        for i in removed_icons.into_iter().filter(|i| *i < segment_count) {
+                            run.segment_mut(i)
+                                .set_icon(livesplit_core::settings::Image::default());
+                        }
+                        load_split_icons(&settings, &mut run);
+                        if let Err(e) = save_run_to_file(&run, &settings) {
+                            return command_error(&e);
+                        }
+                    }
+                    Err(e) => warn!("Icons of run file not updated: {e}"),
+                }
+            }
+            println!("Edited speedrun \"{game}: {category}\"");
+        }
+        "delete" => {
+            if let Err(e) = delete_speedrun_files(&settings) {
+                return command_error(&e);
+            }
+            if let Err(e) = unset_default_speedrun(config, &settings) {
+                return command_error(&e);
+            }
+            println!("Deleted speedrun \"{game}: {category}\"");
+        }
+        "restore" => {
+            if m.is_present("list") {
+                let backups = match list_run_backups(&settings) {
+                    Ok(backups) => backups,
+                    Err(e) => return command_error(&e),
+                };
+                for (timestamp, backup) in backups {
+                    println!("{timestamp}\t{}", backup.display());
+                }
+                return ExitCode::SUCCESS;
+            }
+            match restore_run_backup(&settings, m.value_of("backup")) {
+                Ok(backup) => println!(
+                    "Restored run file of \"{game}: {category}\" from \"{}\"",
+                    backup.display()
+                ),
+                Err(e) => return command_error(&e),
+            }
+        }
+        "set-default" => {
+            if let Err(e) = update_configuration_with_default_speedrun(config, &settings, true) {
+                return command_error(&e);
+            }
+            println!("\"{game}: {category}\" is the default speedrun");
+        }
+        _ => unreachable!("unknown subcommand {subcommand}"),
+    }
+    ExitCode::SUCCESS
+}
+
+/// Print settings of speedrun and summary of its run file
+fn show_speedrun(settings: &SpeedrunSettings) {
+    let optional_key =
+        |key: Option<KeyCode>| key.map_or("not bound".to_string(), |k| format!("{k:?}"));
+    let timing_method = settings.get_timing_method();
+    println!("Game             : {}", settings.get_game_name());
+    println!("Category         : {}", settings.get_category_name());
+    println!(
+        "Splits           : {}",
+        settings.get_split_names().join(" | ")
+    );
+    println!(
+        "Timing method    : {}",
+        match timing_method {
+            TimingMethod::RealTime => "real",
+            TimingMethod::GameTime => "game",
+        }
+    );
+    println!(
+        "Layout           : {}",
+        settings
+            .get_layout_path()
+            .unwrap_or_else(|| "none".to_string())
+    );
+    println!(
+        "Auto splitter    : {}",
+        settings
+            .get_auto_splitter_path()
+            .unwrap_or_else(|| "none".to_string())
+    );
+    println!("Start/split      : {:?}", settings.get_split_key());
+    println!("Reset            : {:?}", settings.get_reset_key());
+    println!("Pause            : {:?}", settings.get_pause_key());
+    println!("Unpause          : {:?}", settings.get_unpause_key());
+    println!("Switch comparison: {:?}", settings.get_comparison_key());
+    println!(
+        "Undo split       : {}",
+        optional_key(settings.get_undo_split_key())
+    );
+    println!(
+        "Skip split       : {}",
+        optional_key(settings.get_skip_split_key())
+    );
+    println!(
+        "Timing method key: {}",
+        optional_key(settings.get_timing_method_key())
+    );
+    println!(
+        "Segment times key: {}",
+        optional_key(settings.get_segment_times_key())
+    );
+    println!(
+        "Practice key     : {}",
+        optional_key(settings.get_practice_key())
+    );
+    println!(
+        "Game time pause  : {}",
+        optional_key(settings.get_game_time_pause_key())
+    );
+    match parse_run_from_file(settings) {
+        Ok(run) => {
+            let personal_best = run
+                .segments()
+                .last()
+                .and_then(|s| s.personal_best_split_time()[timing_method]);
+            println!("Attempts         : {}", run.attempt_count());
+            println!(
+                "Personal best    : {}",
+                personal_best.map_or_else(
+                    || "-".to_string(),
+                    |time| settings.get_time_format().format_time(time)
+                )
+            );
+        }
+        Err(e) => {
+            warn!("Could not parse run file: {e}");
+            println!("Attempts         : 0");
+        }
+    }
+}
+
+/// Log and print error `e` of subcommand
+fn command_error(e: &dyn std::fmt::Display) -> ExitCode {
+    let e = e.to_string();
+    error!("{}", e.trim_end());
+    eprintln!("{}", e.trim_end());
+    ExitCode::FAILURE
+}
+
+/// Create speedrun from splits file given in arguments `m` of import
+/// subcommand, then save its settings and run files
+fn import_speedrun_file(config: PMLSConfiguration, m: &ArgMatches) -> ExitCode {
+    let keybinding = UserKeybinding::new(
+        m.value_of("split-key"),
+        m.value_of("reset-key"),
+        m.value_of("pause-key"),
+        m.value_of("unpause-key"),
+        m.value_of("comparison-key"),
+        m.value_of("undo-split-key"),
+        m.value_of("skip-split-key"),
+        m.value_of("timing-method-key"),
+        m.value_of("segment-times-key"),
+        m.value_of("practice-key"),
+        m.value_of("game-time-pause-key"),
+    );
+    let (settings, run) = match import_speedrun(
+        &config,
+        Path::new(m.value_of("file").unwrap_or_default()),
+        m.value_of("game"),
+        m.value_of("category"),
+        m.value_of("timing-method"),
+        &keybinding,
+    ) {
+        Ok(imported) => imported,
+        Err(e) => return command_error(&e),
+    };
+    if let Err(e) = save_speedrun_settings_to_file(&settings) {
+        return command_error(&e);
+    }
+    if let Err(e) = save_run_to_file(&run, &settings) {
+        return command_error(&e);
+    }
+    if m.is_present("make-speedrun-default") {
+        if let Err(e) = update_configuration_with_default_speedrun(config, &settings, true) {
+            return command_error(&e);
+        }
+    }
+    println!(
+        "Imported speedrun \"{}: {}\" with {} splits",
+        settings.get_game_name(),
+        settings.get_category_name(),
+        settings.get_split_names().len()
+    );
+    ExitCode::SUCCESS
+}
+
+/// Arguments to run a speedrun, creating it if necessary
+fn speedrun_args(accept_help_msg: &str) -> Vec<Arg<'_>> {
+    vec![
+        Arg::new("game")
+            .requires("category")
+            .long("game")
+            .long_help("The game name when loading speedrun (\"GAME/CATEGORY\" is the folder searched in data folder)
+When used with --force-speedrun-settings-creation, provides the name of the game.")
+            .takes_value(true)
+            .value_name("GAME"),
+        Arg::new("category")
+            .requires("game")
+            .long("category")
+            .long_help("The game category name when loading speedrun (\"GAME/CATEGORY\" is the folder searched in data folder)
+When used with --force-speedrun-settings-creation, provides the category name of the game.")
+            .takes_value(true)
+            .value_name("CATEGORY"),
+        Arg::new("split-names")
+            .short('n')
+            .long("split-names")
+            .help("The split names when creating a speedrun")
+            .takes_value(true)
+            .value_name("CATEGORY"),
+        Arg::new("split-key")
+            .short('s')
+            .long("split-key")
+            .help("Assign split key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("SPLIT KEY"),
+        Arg::new("undo-split-key")
+            .long("undo-split-key")
+            .help("Assign undo split key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("UNDO SPLIT KEY"),
+        Arg::new("skip-split-key")
+            .long("skip-split-key")
+            .help("Assign skip split key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("SKIP SPLIT KEY"),
+        Arg::new("reset-key")
+            .short('r')
+            .long("reset-key")
+            .help("Assign reset key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("RESET KEY"),
+        Arg::new("pause-key")
+            .short('p')
+            .long("pause-key")
+            .help("Assign pause key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("PAUSE KEY"),
+        // NOTE: not named resume because short argument conflicts with reset
+        Arg::new("unpause-key")
+            .short('u')
+            .long("unpause-key")
+            .help("Assign unpause key (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("UNPAUSE KEY"),
+        Arg::new("comparison-key")
+            .short('c')
+            .long("comparison-key")
+            .help("Assign comparison key to switch between standard comparisons (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("COMPARISON KEY"),
+        Arg::new("timing-method-key")
+            .long("timing-method-key")
+            .help("Assign key to toggle between real time and game time (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("TIMING METHOD KEY"),
+        Arg::new("segment-times-key")
+            .long("segment-times-key")
+            .help("Assign key to switch between split times and segment times (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("SEGMENT TIMES KEY"),
+        Arg::new("practice-key")
+            .long("practice-key")
+            .help("Assign key to toggle practice mode, where attempts are not recorded (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("PRACTICE KEY"),
+        Arg::new("game-time-pause-key")
+            .long("game-time-pause-key")
+            .help("Assign key to pause or resume game time only, real time keeps running (possible values: https://github.com/LiveSplit/livesplit-core/blob/master/crates/livesplit-hotkey/src/key_code.rs)")
+            .takes_value(true)
+            .value_name("GAME TIME PAUSE KEY"),
+        Arg::new("timing-method")
+            .long("timing-method")
+            .help("Timing method used when loading created speedrun. Game time removes time while paused (loads)")
+            .takes_value(true)
+            .possible_values(["real", "game"])
+            .value_name("TIMING METHOD"),
+        Arg::new("icons")
+            .short('i')
+            .long("icons")
+            .help("Give icon filepath for speedrun creation")
+            .takes_value(true)
+            .multiple_values(true)
+            .value_name("ICON FILEPATH"),
+        Arg::new("layout")
+            .long("layout")
+            .help("Display speedrun with LiveSplit layout file (.lsl or .ls1l). Applies to created and loaded speedrun")
+            .takes_value(true)
+            .value_name("LAYOUT FILEPATH"),
+        Arg::new("auto-splitter")
+            .long("auto-splitter")
+            .help("Control the timer with WebAssembly auto splitter (.wasm). Applies to created and loaded speedrun")
+            .takes_value(true)
+            .value_name("AUTO SPLITTER FILEPATH"),
+        Arg::new("server-port")
+            .long("server-port")
+            .help("Start LiveSplit Server on localhost port (overrides port of configuration file)")
+            .takes_value(true)
+            .validator(|p| p.parse::<u16>())
+            .value_name("PORT"),
+        Arg::new("state-feed-port")
+            .long("state-feed-port")
+            .help("Publish timer state for streaming overlays on localhost port (overrides port of configuration file)")
+            .takes_value(true)
+            .validator(|p| p.parse::<u16>())
+            .value_name("PORT"),
+        Arg::new("text-outputs")
+            .long("text-outputs")
+            .help("Write timer data to text files of the outputs folder for streaming software (overrides outputs of configuration file)")
+            .takes_value(true)
+            .multiple_values(true)
+            .possible_values(TextOutput::ALL.map(TextOutput::name))
+            .value_name("OUTPUT"),
+        Arg::new("tui")
+            .long("tui")
+            .alias("headless")
+            .help("Display timer in the terminal instead of a window (always the case when built without the gui feature)"),
+        Arg::new("practice")
+            .long("practice")
+            .help("Start in practice mode: attempts are not recorded and the run file is never written"),
+        Arg::new("practice-from")
+            .long("practice-from")
+            .help("Practice segments starting at this split (name or number starting at 1). Implies --practice")
+            .takes_value(true)
+            .value_name("SPLIT"),
+        Arg::new("practice-to")
+            .long("practice-to")
+            .help("Last practiced split (name or number starting at 1), --practice-from split if absent")
+            .takes_value(true)
+            .requires("practice-from")
+            .value_name("SPLIT"),
+        Arg::new("recover-attempt")
+            .long("recover-attempt")
+            .help("Resume the attempt interrupted by a crash, or add it to the attempt history (reset). Asked when absent")
+            .takes_value(true)
+            .possible_values(["resume", "reset"])
+            .value_name("CHOICE"),
+        Arg::new("accept-automatically-configuration-creation")
+            .long("accept-automatically-configuration-creation")
+            .help(accept_help_msg),
+        Arg::new("force-speedrun-settings-creation")
+            .long("force-speedrun-settings-creation")
+            .help("Avoid behavior where program defaults to finding speedrun by name."),
+        Arg::new("make-speedrun-default")
+            .long("make-speedrun-default")
+            .help("Make created speedrun default"),
+    ]
+}
+
 /// When something wrong happens, inform user to check logs before closing the
 /// program
 fn exit_error_message(appname: &str) {
-    if let Err(e) = dialog::Message::new(format!(
-        "Please check out the logs at `$HOME/.{appname}/logs.txt`"
-    ))
-    .title("An error has happened. Closing application.")
-    .show()
+    let log_file_path = default_log_file_path().map_or_else(
+        |_| format!("$XDG_STATE_HOME/{appname}/logs.txt"),
+        |path| path.display().to_string(),
+    );
+    if let Err(e) = dialog::Message::new(format!("Please check out the logs at `{log_file_path}`"))
+        .title("An error has happened. Closing application.")
+        .show()
     {
         error!("{e}")
     }
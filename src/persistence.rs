@@ -1,24 +1,42 @@
 //! Interact with user to parse and persist necessary configurations files:
 //!
-//! * run file (.lss)
-//! * settings (.txt) associated with speedrun
-//! * general configuration (`$HOME/.config/.pmls`)
-//! * log file
-use crate::Keybinding;
+//! * run file (`run.lss`)
+//! * settings (`settings.toml`) associated with speedrun
+//! * index of speedruns (`index.toml`)
+//! * split icons (`icons/`) referenced by speedrun settings
+//! * layout file (.lsl or .ls1l) referenced by speedrun settings
+//! * general configuration (`$XDG_CONFIG_HOME/pmls/config.toml`)
+//! * log file (`$XDG_STATE_HOME/pmls/logs.txt`)
+//!
+//! Files of each speedrun are stored in their own `<game>/<category>` folder of
+//! the data folder of the general configuration (`$XDG_DATA_HOME/pmls` by
+//! default)
+use crate::text_outputs::TextOutput;
+use crate::time_format::TimeFormat;
+use crate::{DeltaColors, Keybinding, ResetPolicy, SummaryRows};
 use clap::Values;
 use dialog::{DialogBox, Input};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageOutputFormat};
 use itertools::Itertools;
 use livesplit_core::hotkey::KeyCode;
-use livesplit_core::hotkey::KeyCode::{Numpad1, Numpad3, Numpad5, Numpad7, Numpad9};
+use livesplit_core::hotkey::KeyCode::{
+    Numpad1, Numpad2, Numpad3, Numpad5, Numpad7, Numpad8, Numpad9,
+};
+use livesplit_core::layout::{self, LayoutSettings};
 use livesplit_core::run::{parser::composite, saver::livesplit};
-use livesplit_core::Run;
+use livesplit_core::settings::Image;
+use livesplit_core::{AtomicDateTime, Layout, Run, TimingMethod};
 use log::{debug, error, info, trace, warn};
 use serde::{Deserialize, Serialize};
 use std::env::VarError;
 use std::fmt::Debug;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
-use std::path::{Path, PathBuf};
+use std::io::{BufReader, Cursor, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 use std::{fmt, fs};
 use walkdir::WalkDir;
 
@@ -28,16 +46,93 @@ pub struct PMLSConfiguration {
     data_folder_path: String,
     /// open default speedrun when launching application with no arguments
     use_default_speedrun: bool,
+    /// folder of default speedrun, relative to the data folder
     default_speedrun_name: Option<String>,
+    /// accept livesplit server commands from local tools
+    #[serde(default)]
+    livesplit_server: bool,
+    #[serde(default = "default_livesplit_server_port")]
+    livesplit_server_port: u16,
+    /// publish timer state for streaming overlays
+    #[serde(default)]
+    state_feed: bool,
+    #[serde(default = "default_state_feed_port")]
+    state_feed_port: u16,
+    /// timer data written to text files of the outputs folder for streaming
+    /// software (`current_time`, `current_split`, `delta`, `personal_best`,
+    /// `sum_of_best`, `attempt_count`)
+    #[serde(default)]
+    text_outputs: Vec<TextOutput>,
+    /// seconds between checks for unsaved run changes, 0 disables autosave
+    #[serde(default = "default_autosave_interval_secs")]
+    autosave_interval_secs: u64,
+    /// path of the configuration file
+    #[serde(skip)]
+    path: PathBuf,
+    /// data folder provided by the user, used instead of `data_folder_path`
+    #[serde(skip)]
+    data_folder_override: Option<PathBuf>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 /// Settings for speedrun
 pub struct SpeedrunSettings {
     split_names: Vec<String>,
     game_name: String,
     category_name: String,
+    /// timing method selected when the speedrun is loaded
+    #[serde(default = "default_timing_method")]
+    timing_method: TimingMethod,
+    /// layout file (.lsl or .ls1l) used to display the speedrun
+    #[serde(default)]
+    layout_path: Option<String>,
+    /// WebAssembly auto splitter (.wasm) controlling the timer
+    #[serde(default)]
+    auto_splitter_path: Option<String>,
+    /// number of run file backups kept in the backups folder
+    #[serde(default = "default_run_backups")]
+    run_backups: usize,
+    /// icons of splits, relative to the speedrun folder (empty when the split
+    /// has no icon)
+    #[serde(default)]
+    split_icons: Vec<String>,
+    // NOTE: tables are serialized after values in toml
     keybindings: Keybinding,
+    /// rows displayed below the splits
+    #[serde(default)]
+    summary_rows: SummaryRows,
+    /// colors of time differences
+    #[serde(default)]
+    delta_colors: DeltaColors,
+    /// format of displayed times
+    #[serde(default)]
+    time_format: TimeFormat,
+    /// confirmations asked when resetting
+    #[serde(default)]
+    reset_policy: ResetPolicy,
+    /// folder of speedrun files
+    #[serde(skip)]
+    data_folder: PathBuf,
+}
+
+/// Name of the index of speedruns in the data folder
+const INDEX_FILE_NAME: &str = "index.toml";
+
+#[derive(Serialize, Deserialize, Default)]
+/// Index of speedruns of the data folder, so speedruns are found without
+/// walking the data folder
+struct SpeedrunIndex {
+    #[serde(default)]
+    speedruns: Vec<IndexedSpeedrun>,
+}
+
+#[derive(Serialize, Deserialize)]
+/// Speedrun of the index
+struct IndexedSpeedrun {
+    game_name: String,
+    category_name: String,
+    /// folder of speedrun files, relative to the data folder
+    folder: String,
 }
 
 /// Keybinding provided by the user from cli args
@@ -48,6 +143,12 @@ pub struct UserKeybinding<'a> {
     pause_key: Option<&'a str>,
     unpause_key: Option<&'a str>,
     comparison_key: Option<&'a str>,
+    undo_split_key: Option<&'a str>,
+    skip_split_key: Option<&'a str>,
+    timing_method_key: Option<&'a str>,
+    segment_times_key: Option<&'a str>,
+    practice_key: Option<&'a str>,
+    game_time_pause_key: Option<&'a str>,
 }
 
 #[derive(Debug)]
@@ -73,6 +174,8 @@ pub enum ConfigurationFileError {
     UserInput(String),
     /// User did not accept creating necessary configuration file
     UserCancel(),
+    /// Missing configuration file
+    Missing(String),
     /// Error with the user environment variables
     VarError(VarError),
     /// User cannot interact with dialog box
@@ -115,6 +218,8 @@ pub enum SpeedrunSettingsFileError {
     Other(String),
     /// Missing settings file
     Missing(String),
+    /// Cannot read or write split icon
+    Image(image::ImageError),
 }
 
 /// Errors while using the run file of a speedrun
@@ -124,7 +229,7 @@ pub enum RunFileError {
     /// User did not provide asked input
     UserCancel(),
     /// Cannot save `.lss` file
-    Save(livesplit_core::run::saver::livesplit::Error),
+    Save(std::fmt::Error),
     /// Cannot parse `.lss` file
     Parse(livesplit_core::run::parser::composite::Error),
     /// Error with the user environment variables
@@ -137,19 +242,39 @@ pub enum RunFileError {
     Other(String),
 }
 
+/// Errors while using the layout file of a speedrun
+pub enum LayoutFileError {
+    /// Layout file is neither a `.lsl` nor a `.ls1l` file
+    UnknownFormat(String),
+    /// Cannot parse `.lsl` file
+    Parse(livesplit_core::layout::parser::Error),
+    /// Cannot parse `.ls1l` file
+    ParseSettings(serde_json::Error),
+    /// Error with filesystem
+    IO(std::io::Error),
+}
+
 impl<'a> SpeedrunSettings {
     fn new(
         split_names: Vec<String>,
         game_name: String,
         category_name: String,
         keybindings: Keybinding,
+        timing_method: TimingMethod,
+        data_folder: PathBuf,
     ) -> Result<SpeedrunSettings, SpeedrunSettingsFileError> {
-        let keys = vec![
+        let mut keys = vec![
             keybindings.split_key,
             keybindings.reset_key,
             keybindings.pause_key,
             keybindings.unpause_key,
         ];
+        keys.extend(keybindings.undo_split_key);
+        keys.extend(keybindings.skip_split_key);
+        keys.extend(keybindings.timing_method_key);
+        keys.extend(keybindings.segment_times_key);
+        keys.extend(keybindings.practice_key);
+        keys.extend(keybindings.game_time_pause_key);
         if !keys.iter().all_unique() {
             return Err(SpeedrunSettingsFileError::UserInput(
                 "All keys need to be bound to a different key".to_string(),
@@ -160,18 +285,106 @@ impl<'a> SpeedrunSettings {
             game_name,
             category_name,
             keybindings,
+            timing_method,
+            layout_path: None,
+            auto_splitter_path: None,
+            summary_rows: SummaryRows::default(),
+            delta_colors: DeltaColors::default(),
+            time_format: TimeFormat::default(),
+            reset_policy: ResetPolicy::default(),
+            run_backups: default_run_backups(),
+            split_icons: vec![],
+            data_folder,
         })
     }
 }
 
 impl<'a> PMLSConfiguration {
-    fn new() -> Result<PMLSConfiguration, ConfigurationFileError> {
+    fn new(
+        path: &Path,
+        data_folder: Option<&Path>,
+    ) -> Result<PMLSConfiguration, ConfigurationFileError> {
         Ok(PMLSConfiguration {
-            data_folder_path: default_data_folder()?,
+            data_folder_path: default_data_folder()?.display().to_string(),
             default_speedrun_name: None,
             use_default_speedrun: true,
+            livesplit_server: false,
+            livesplit_server_port: default_livesplit_server_port(),
+            state_feed: false,
+            state_feed_port: default_state_feed_port(),
+            text_outputs: vec![],
+            autosave_interval_secs: default_autosave_interval_secs(),
+            path: path.to_path_buf(),
+            data_folder_override: data_folder.map(Path::to_path_buf),
         })
     }
+
+    /// Returns folder of speedrun files
+    #[must_use]
+    pub fn get_data_folder(&self) -> PathBuf {
+        match &self.data_folder_override {
+            Some(data_folder) => data_folder.clone(),
+            None => PathBuf::from(self.data_folder_path.as_str()),
+        }
+    }
+
+    /// Returns true if livesplit server should be started
+    #[must_use]
+    pub fn is_livesplit_server_enabled(&self) -> bool {
+        self.livesplit_server
+    }
+
+    /// Returns port livesplit server listens on
+    #[must_use]
+    pub fn get_livesplit_server_port(&self) -> u16 {
+        self.livesplit_server_port
+    }
+
+    /// Returns true if state feed for streaming overlays should be started
+    #[must_use]
+    pub fn is_state_feed_enabled(&self) -> bool {
+        self.state_feed
+    }
+
+    /// Returns port state feed listens on
+    #[must_use]
+    pub fn get_state_feed_port(&self) -> u16 {
+        self.state_feed_port
+    }
+
+    /// Returns timer data to write to text files
+    #[must_use]
+    pub fn get_text_outputs(&self) -> Vec<TextOutput> {
+        self.text_outputs.clone()
+    }
+
+    /// Returns folder where text outputs are written
+    #[must_use]
+    pub fn get_text_outputs_folder(&self) -> PathBuf {
+        self.get_data_folder().join("outputs")
+    }
+
+    /// Returns delay between checks for unsaved run changes, if autosave is
+    /// enabled
+    #[must_use]
+    pub fn get_autosave_interval(&self) -> Option<Duration> {
+        (self.autosave_interval_secs > 0).then(|| Duration::from_secs(self.autosave_interval_secs))
+    }
+}
+
+/// Port of livesplit server when not configured
+fn default_livesplit_server_port() -> u16 {
+    crate::server::DEFAULT_PORT
+}
+
+/// Port of state feed when not configured
+fn default_state_feed_port() -> u16 {
+    crate::state_feed::DEFAULT_PORT
+}
+
+/// Autosave interval when not configured
+fn default_autosave_interval_secs() -> u64 {
+    5
 }
 
 impl<'a> fmt::Display for ConfigurationFileError {
@@ -183,6 +396,9 @@ impl<'a> fmt::Display for ConfigurationFileError {
             ConfigurationFileError::UserCancel() => {
                 writeln!(f, "User cancelled creating general configuration file")
             }
+            ConfigurationFileError::Missing(path) => {
+                writeln!(f, "No configuration file was found at \"{path}\"")
+            }
             ConfigurationFileError::Dialog(de) => fmt::Display::fmt(de, f),
             ConfigurationFileError::IO(ioe) => fmt::Display::fmt(ioe, f),
             ConfigurationFileError::DataFolder(wde) => fmt::Display::fmt(wde, f),
@@ -248,8 +464,8 @@ impl<'a> From<livesplit_core::run::parser::composite::Error> for RunFileError {
     }
 }
 
-impl<'a> From<livesplit_core::run::saver::livesplit::Error> for RunFileError {
-    fn from(e: livesplit_core::run::saver::livesplit::Error) -> Self {
+impl<'a> From<std::fmt::Error> for RunFileError {
+    fn from(e: std::fmt::Error) -> Self {
         RunFileError::Save(e)
     }
 }
@@ -297,6 +513,12 @@ impl<'a> From<std::io::Error> for SpeedrunSettingsFileError {
     }
 }
 
+impl From<image::ImageError> for SpeedrunSettingsFileError {
+    fn from(e: image::ImageError) -> Self {
+        SpeedrunSettingsFileError::Image(e)
+    }
+}
+
 impl<'a> From<&str> for SpeedrunSettingsFileError {
     fn from(e: &str) -> Self {
         SpeedrunSettingsFileError::OSStringConversion(e.to_string())
@@ -361,8 +583,9 @@ impl fmt::Display for SpeedrunSettingsFileError {
             SpeedrunSettingsFileError::Deserialize(de) => fmt::Display::fmt(de, f),
             SpeedrunSettingsFileError::IO(ioe) => fmt::Display::fmt(ioe, f),
             SpeedrunSettingsFileError::Missing(filename) => {
-                writeln!(f, "Missing settings file with name: \"{filename}\"")
+                writeln!(f, "No speedrun settings in folder \"{filename}\"")
             }
+            SpeedrunSettingsFileError::Image(ie) => fmt::Display::fmt(ie, f),
         }
     }
 }
@@ -384,6 +607,38 @@ impl<'a> fmt::Display for RunFileError {
     }
 }
 
+impl fmt::Display for LayoutFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutFileError::UnknownFormat(path) => writeln!(
+                f,
+                "Layout file \"{path}\" must have .lsl or .ls1l extension"
+            ),
+            LayoutFileError::Parse(pe) => fmt::Display::fmt(pe, f),
+            LayoutFileError::ParseSettings(se) => fmt::Display::fmt(se, f),
+            LayoutFileError::IO(ioe) => fmt::Display::fmt(ioe, f),
+        }
+    }
+}
+
+impl From<std::io::Error> for LayoutFileError {
+    fn from(e: std::io::Error) -> Self {
+        LayoutFileError::IO(e)
+    }
+}
+
+impl From<livesplit_core::layout::parser::Error> for LayoutFileError {
+    fn from(e: livesplit_core::layout::parser::Error) -> Self {
+        LayoutFileError::Parse(e)
+    }
+}
+
+impl From<serde_json::Error> for LayoutFileError {
+    fn from(e: serde_json::Error) -> Self {
+        LayoutFileError::ParseSettings(e)
+    }
+}
+
 impl<'a> fmt::Display for FileError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -399,12 +654,19 @@ impl<'a> fmt::Display for FileError {
 impl<'a> UserKeybinding<'_> {
     /// Represents keybindings provided by the user
     #[must_use]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         split_key: Option<&'a str>,
         reset_key: Option<&'a str>,
         pause_key: Option<&'a str>,
         unpause_key: Option<&'a str>,
         comparison_key: Option<&'a str>,
+        undo_split_key: Option<&'a str>,
+        skip_split_key: Option<&'a str>,
+        timing_method_key: Option<&'a str>,
+        segment_times_key: Option<&'a str>,
+        practice_key: Option<&'a str>,
+        game_time_pause_key: Option<&'a str>,
     ) -> UserKeybinding<'a> {
         UserKeybinding {
             split_key,
@@ -412,6 +674,12 @@ impl<'a> UserKeybinding<'_> {
             pause_key,
             unpause_key,
             comparison_key,
+            undo_split_key,
+            skip_split_key,
+            timing_method_key,
+            segment_times_key,
+            practice_key,
+            game_time_pause_key,
         }
     }
 }
@@ -422,8 +690,11 @@ impl<'a> UserKeybinding<'_> {
 // TODO support macos
 //
 // Note: can't use $HOME as is
-/// Returns "$HOME/.config/.pmls" expanded
-fn default_config_path() -> Result<String, ConfigurationFileError> {
+/// Returns `$XDG_CONFIG_HOME/pmls/config.toml` expanded
+///
+/// # Errors
+/// Returns an error if the $HOME variable cannot be infered
+pub fn default_config_path() -> Result<PathBuf, ConfigurationFileError> {
     // NOTE: using std::env::var(HOME) with sudo gives root which is bad.
     // `su <sudoer USER>` works with desired user but you also give root access
     // which is also bad.
@@ -461,41 +732,164 @@ fn default_config_path() -> Result<String, ConfigurationFileError> {
     // ATTRS{serial}=="0000:00:14.0"
     // sudo udevadm info --attribute-walk --name /dev/input/event8 | grep name
     // ATTRS{name}=="Logitech Gaming Keyboard G213 Keyboard"
-    let home = std::env::var("HOME")?;
     let binary_name = env!("CARGO_PKG_NAME");
-    Ok(format!("{home}/.config/.{binary_name}"))
+    Ok(base_directory("XDG_CONFIG_HOME", ".config")?
+        .join(binary_name)
+        .join("config.toml"))
 }
 
-/// Returns "$HOME/.pmls" expanded
+/// Returns `$XDG_DATA_HOME/pmls` expanded
 ///
 /// # Errors
 /// Returns an error if the $HOME variable cannot be infered
-pub fn default_data_folder() -> Result<String, FileError> {
+pub fn default_data_folder() -> Result<PathBuf, FileError> {
     // Note: if executed with sudo, home will default to /root, which is usually not desired
-    let home = std::env::var("HOME")?;
     let binary_name = env!("CARGO_PKG_NAME");
-    Ok(format!("{home}/.{binary_name}"))
+    Ok(base_directory("XDG_DATA_HOME", ".local/share")?.join(binary_name))
 }
 
-/// Returns "$HOME/.pmls/logs.txt"
+/// Returns `$XDG_STATE_HOME/pmls/logs.txt` expanded
 ///
 /// # Errors
 /// Returns an error if the $HOME variable cannot be infered
-pub fn default_log_file_path() -> Result<String, FileError> {
+pub fn default_log_file_path() -> Result<PathBuf, FileError> {
+    let binary_name = env!("CARGO_PKG_NAME");
+    Ok(base_directory("XDG_STATE_HOME", ".local/state")?
+        .join(binary_name)
+        .join("logs.txt"))
+}
+
+/// Returns XDG base directory set in `variable`, or `$HOME/<fallback>` when
+/// unset. Relative paths are ignored as required by the XDG specification
+fn base_directory(variable: &str, fallback: &str) -> Result<PathBuf, VarError> {
+    if let Some(directory) = std::env::var_os(variable).map(PathBuf::from) {
+        if directory.is_absolute() {
+            return Ok(directory);
+        }
+    }
+    let home = std::env::var("HOME")?;
+    Ok(Path::new(home.as_str()).join(fallback))
+}
+
+/// Move configuration file and data folder from their locations before XDG
+/// base directories were used (`$HOME/.config/.pmls` and `$HOME/.pmls`). Files
+/// already present at the new locations are never overwritten
+///
+/// # Errors
+/// Returns an error if files cannot be moved
+pub fn migrate_legacy_files() -> Result<(), ConfigurationFileError> {
     let home = std::env::var("HOME")?;
     let binary_name = env!("CARGO_PKG_NAME");
-    Ok(format!("{home}/.{binary_name}/logs.txt"))
+    let legacy_config_path = Path::new(home.as_str())
+        .join(".config")
+        .join(format!(".{binary_name}"));
+    let legacy_data_folder = Path::new(home.as_str()).join(format!(".{binary_name}"));
+    migrate_files(
+        &legacy_config_path,
+        &legacy_data_folder,
+        default_config_path()?,
+        &default_data_folder()?,
+    )
+}
+
+/// Move configuration file at `legacy_config_path` to `config_path` and data
+/// folder at `legacy_data_folder` to `data_folder`. The configuration only
+/// points to `data_folder` once the data folder was moved
+///
+/// # Errors
+/// Returns an error if files cannot be moved
+fn migrate_files(
+    legacy_config_path: &Path,
+    legacy_data_folder: &Path,
+    config_path: PathBuf,
+    data_folder: &Path,
+) -> Result<(), ConfigurationFileError> {
+    // NOTE: data folder is moved first, so that the configuration never points
+    //       to a folder without the speedruns
+    if legacy_data_folder.is_dir() {
+        if data_folder.exists() {
+            warn!(
+                "Not moving data folder \"{}\": \"{}\" already exists",
+                legacy_data_folder.display(),
+                data_folder.display()
+            );
+        } else {
+            info!(
+                "Moving data folder \"{}\" to \"{}\"",
+                legacy_data_folder.display(),
+                data_folder.display()
+            );
+            if let Some(parent) = data_folder.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            if let Err(e) = fs::rename(legacy_data_folder, data_folder) {
+                return Err(ConfigurationFileError::Other(format!(
+                    "Could not move data folder \"{}\" to \"{}\" ({e}). Please move it manually",
+                    legacy_data_folder.display(),
+                    data_folder.display()
+                )));
+            }
+        }
+    }
+
+    if legacy_config_path.is_file() && !config_path.exists() {
+        info!(
+            "Moving configuration file \"{}\" to \"{}\"",
+            legacy_config_path.display(),
+            config_path.display()
+        );
+        let content = fs::read_to_string(legacy_config_path)?;
+        let mut config: PMLSConfiguration = toml::from_str(content.as_str())?;
+        config.path = config_path;
+        if Path::new(config.data_folder_path.as_str()) == legacy_data_folder
+            && !legacy_data_folder.exists()
+        {
+            config.data_folder_path = data_folder.display().to_string();
+        }
+        save_config_to_file(&config)?;
+        fs::remove_file(legacy_config_path)?;
+    }
+    Ok(())
 }
 
 impl SpeedrunSettings {
-    /// Get file name of speedrun settings
-    fn get_file_name(&self) -> String {
-        format!("{}_{}.txt", self.game_name, self.category_name)
+    /// Get folder of speedrun files, relative to the data folder
+    fn get_folder_name(&self) -> String {
+        speedrun_folder_name(self.game_name.as_str(), self.category_name.as_str())
+    }
+
+    /// Get folder of speedrun files
+    fn get_folder(&self) -> PathBuf {
+        self.data_folder.join(self.get_folder_name())
+    }
+
+    /// Get path of speedrun settings
+    fn get_file_path(&self) -> PathBuf {
+        self.get_folder().join("settings.toml")
+    }
+
+    /// Get path of associated run file
+    fn get_run_file_path(&self) -> PathBuf {
+        self.get_folder().join("run.lss")
     }
 
-    /// Get file name of associated run file
-    fn get_run_file_name(&self) -> String {
-        format!("{}_{}.lss", self.game_name, self.category_name)
+    /// Get folder of associated run file backups
+    fn get_backups_folder(&self) -> PathBuf {
+        self.get_folder().join("backups")
+    }
+
+    /// Get path of icon of split `i`, relative to the speedrun folder
+    fn get_split_icon_file_name(i: usize) -> String {
+        format!("icons/{}.png", i + 1)
+    }
+
+    /// Get path of split icon `icon` if it is inside the speedrun folder
+    fn get_split_icon_path(&self, icon: &str) -> Option<PathBuf> {
+        let is_inside = !icon.is_empty()
+            && Path::new(icon)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+        is_inside.then(|| self.get_folder().join(icon))
     }
 
     /// Return the name of the game for this speedrun
@@ -545,9 +939,138 @@ impl SpeedrunSettings {
     pub fn get_comparison_key(&self) -> KeyCode {
         self.keybindings.comparison_key
     }
+
+    /// Get undo split key from this speedrun settings if bound
+    #[must_use]
+    pub fn get_undo_split_key(&self) -> Option<KeyCode> {
+        self.keybindings.undo_split_key
+    }
+
+    /// Get skip split key from this speedrun settings if bound
+    #[must_use]
+    pub fn get_skip_split_key(&self) -> Option<KeyCode> {
+        self.keybindings.skip_split_key
+    }
+
+    /// Get timing method toggle key from this speedrun settings if bound
+    #[must_use]
+    pub fn get_timing_method_key(&self) -> Option<KeyCode> {
+        self.keybindings.timing_method_key
+    }
+
+    /// Get segment times toggle key from this speedrun settings if bound
+    #[must_use]
+    pub fn get_segment_times_key(&self) -> Option<KeyCode> {
+        self.keybindings.segment_times_key
+    }
+
+    /// Get practice mode toggle key from this speedrun settings if bound
+    #[must_use]
+    pub fn get_practice_key(&self) -> Option<KeyCode> {
+        self.keybindings.practice_key
+    }
+
+    /// Get game time pause toggle key from this speedrun settings if bound
+    #[must_use]
+    pub fn get_game_time_pause_key(&self) -> Option<KeyCode> {
+        self.keybindings.game_time_pause_key
+    }
+
+    /// Return the timing method used when loading this speedrun
+    #[must_use]
+    pub fn get_timing_method(&self) -> TimingMethod {
+        self.timing_method
+    }
+
+    /// Return the layout file path used to display this speedrun if any
+    #[must_use]
+    pub fn get_layout_path(&self) -> Option<String> {
+        self.layout_path.clone()
+    }
+
+    /// Return the auto splitter file path controlling the timer if any
+    #[must_use]
+    pub fn get_auto_splitter_path(&self) -> Option<String> {
+        self.auto_splitter_path.clone()
+    }
+
+    /// Return the summary rows displayed below the splits
+    #[must_use]
+    pub fn get_summary_rows(&self) -> SummaryRows {
+        self.summary_rows
+    }
+
+    /// Return the colors of time differences
+    #[must_use]
+    pub fn get_delta_colors(&self) -> DeltaColors {
+        self.delta_colors.clone()
+    }
+
+    /// Return the format of displayed times
+    #[must_use]
+    pub fn get_time_format(&self) -> TimeFormat {
+        self.time_format
+    }
+
+    /// Return the confirmations asked when resetting
+    #[must_use]
+    pub fn get_reset_policy(&self) -> ResetPolicy {
+        self.reset_policy
+    }
+
+    /// Display this speedrun with layout file at `layout_path`. The absolute
+    /// path is stored, so the speedrun can be launched from any folder
+    ///
+    /// # Errors
+    /// Returns an error if there is no file at `layout_path`
+    pub fn set_layout_path(&mut self, layout_path: &str) -> Result<(), SpeedrunSettingsFileError> {
+        self.layout_path = Some(absolute_file_path(layout_path, "Layout")?);
+        Ok(())
+    }
+
+    /// Control the timer of this speedrun with auto splitter at
+    /// `auto_splitter_path`. The absolute path is stored, so the speedrun can
+    /// be launched from any folder
+    ///
+    /// # Errors
+    /// Returns an error if there is no file at `auto_splitter_path`
+    pub fn set_auto_splitter_path(
+        &mut self,
+        auto_splitter_path: &str,
+    ) -> Result<(), SpeedrunSettingsFileError> {
+        self.auto_splitter_path = Some(absolute_file_path(auto_splitter_path, "Auto splitter")?);
+        Ok(())
+    }
+}
+
+/// Returns absolute path of existing file at `path`, described as
+/// `description` in errors
+fn absolute_file_path(path: &str, description: &str) -> Result<String, SpeedrunSettingsFileError> {
+    let absolute_path = match fs::canonicalize(path) {
+        Ok(p) if p.is_file() => p,
+        Ok(_) => {
+            return Err(SpeedrunSettingsFileError::UserInput(format!(
+                "{description} \"{path}\" is not a file"
+            )))
+        }
+        Err(e) => {
+            return Err(SpeedrunSettingsFileError::UserInput(format!(
+                "{description} \"{path}\": {e}"
+            )))
+        }
+    };
+    match absolute_path.to_str() {
+        Some(p) => Ok(p.to_string()),
+        None => Err(SpeedrunSettingsFileError::OSStringConversion(format!(
+            "{description} path \"{}\" is not valid unicode",
+            absolute_path.display()
+        ))),
+    }
 }
 
-/// Parse configuration file at default path and ask user if not present
+/// Parse configuration file at `config_path` and ask user if not present.
+/// Speedrun files are stored in `data_folder` if provided, otherwise in the
+/// data folder of the configuration
 ///
 /// Accept and skip dialog if `accept_automatically_configuration_creation` is
 /// `true`.
@@ -557,48 +1080,79 @@ impl SpeedrunSettings {
 /// with the dialog box
 pub fn parse_configuration(
     accept_automatically_configuration_creation: bool,
+    config_path: &Path,
+    data_folder: Option<&Path>,
 ) -> Result<PMLSConfiguration, ConfigurationFileError> {
-    let default_config_path = default_config_path()?;
-    let default_data_folder = default_data_folder()?;
-    if !Path::new(default_config_path.as_str()).exists() {
-        if accept_automatically_configuration_creation {
-            let config = PMLSConfiguration::new()?;
-            match save_config_to_file(&config) {
-                Ok(()) => return Ok(config),
-                Err(e) => return Err(e),
-            };
-        }
-
-        let choice = dialog::Question::new(format!("No configuration file was found at \"{default_config_path}\". Create configuration file?").as_str())
-    .title("Create configuration file?")
-    .show()?;
-        if choice == dialog::Choice::Yes {
-            let config = PMLSConfiguration::new()?;
-            match save_config_to_file(&config) {
-                Ok(()) => return Ok(config),
-                Err(e) => return Err(e),
-            };
+    if !config_path.exists() {
+        if !accept_automatically_configuration_creation {
+            let choice = dialog::Question::new(
+                format!(
+                    "No configuration file was found at \"{}\". Create configuration file?",
+                    config_path.display()
+                )
+                .as_str(),
+            )
+            .title("Create configuration file?")
+            .show()?;
+            if choice != dialog::Choice::Yes {
+                return Err(ConfigurationFileError::UserCancel());
+            }
         }
-        return Err(ConfigurationFileError::UserCancel());
+        let config = PMLSConfiguration::new(config_path, data_folder)?;
+        save_config_to_file(&config)?;
+        fs::create_dir_all(config.get_data_folder())?;
+        return Ok(config);
     }
 
-    if !Path::new(default_data_folder.as_str()).exists() {
-        let choice = dialog::Question::new(
-            format!("No data folder was found at \"{default_data_folder}\". Create data folder?")
+    let config = read_configuration(config_path, data_folder)?;
+    let data_folder = config.get_data_folder();
+    if !data_folder.exists() {
+        let choice = if accept_automatically_configuration_creation {
+            dialog::Choice::Yes
+        } else {
+            dialog::Question::new(
+                format!(
+                    "No data folder was found at \"{}\". Create data folder?",
+                    data_folder.display()
+                )
                 .as_str(),
-        )
-        .title("Create data folder?")
-        .show()?;
+            )
+            .title("Create data folder?")
+            .show()?
+        };
         if choice == dialog::Choice::Yes {
-            fs::create_dir(default_data_folder.as_str())?;
+            fs::create_dir_all(data_folder)?;
         }
     }
+    Ok(config)
+}
 
+/// Parse configuration file at `config_path` without asking user. Speedrun
+/// files are stored in `data_folder` if provided
+///
+/// # Errors
+/// Returns an error if configuration file is missing or cannot be parsed
+pub fn read_configuration(
+    config_path: &Path,
+    data_folder: Option<&Path>,
+) -> Result<PMLSConfiguration, ConfigurationFileError> {
+    if !config_path.exists() {
+        return Err(ConfigurationFileError::Missing(
+            config_path.display().to_string(),
+        ));
+    }
     trace!("Parsing configuration file");
-    let mut file = File::open(default_config_path.as_str())?;
+    let mut file = File::open(config_path)?;
     let mut config = String::new();
     file.read_to_string(&mut config)?;
-    let config: PMLSConfiguration = toml::from_str(config.as_str())?;
+    let mut config: PMLSConfiguration = toml::from_str(config.as_str())?;
+    config.path = config_path.to_path_buf();
+    config.data_folder_override = data_folder.map(Path::to_path_buf);
+    // speedruns are indexed once they are stored in their own folder
+    let data_folder = config.get_data_folder();
+    if data_folder.is_dir() && !data_folder.join(INDEX_FILE_NAME).exists() {
+        migrate_speedrun_files(&mut config)?;
+    }
     Ok(config)
 }
 
@@ -611,11 +1165,13 @@ pub fn parse_configuration(
 /// # Errors
 /// Returns an error when speedrun settings cannot be loaded, user provided
 /// arguments are invalid or when user stops filling speedrun settings
+#[allow(clippy::too_many_arguments)]
 pub fn load_speedrun_settings<'a>(
     configuration: &'a PMLSConfiguration,
     game_name: Option<&str>,
     category_name: Option<&str>,
     split_names: Option<&str>,
+    timing_method: Option<&str>,
     user_keybinding: UserKeybinding,
     icons: Option<Values>,
     force_speedrun_settings_creation: bool,
@@ -624,20 +1180,8 @@ pub fn load_speedrun_settings<'a>(
         // look for speedrun file using `game_name` and `category_name`
         if let Some(game_name) = game_name {
             if let Some(category_name) = category_name {
-                let settings = SpeedrunSettings {
-                    game_name: game_name.to_string(),
-                    category_name: category_name.to_string(),
-                    split_names: vec![],
-                    keybindings: Keybinding {
-                        split_key: KeyCode::Numpad1,
-                        reset_key: KeyCode::Numpad3,
-                        pause_key: KeyCode::Numpad5,
-                        unpause_key: KeyCode::Numpad7,
-                        comparison_key: KeyCode::Numpad9,
-                    },
-                };
                 return Ok((
-                    find_speedrun_by_name(settings.get_file_name(), configuration)?,
+                    find_speedrun(configuration, game_name, category_name)?,
                     None,
                     false,
                 ));
@@ -652,9 +1196,11 @@ pub fn load_speedrun_settings<'a>(
             None => {
                 warn!("No default speedrun name was set. Have you set a default_speedrun_name entry in your configuration file?");
                 match ask_speedrun_settings_to_user(
+                    configuration.get_data_folder(),
                     game_name,
                     category_name,
                     split_names,
+                    timing_method,
                     &user_keybinding,
                     icons,
                 ) {
@@ -665,9 +1211,11 @@ pub fn load_speedrun_settings<'a>(
         }
     } else {
         match ask_speedrun_settings_to_user(
+            configuration.get_data_folder(),
             game_name,
             category_name,
             split_names,
+            timing_method,
             &user_keybinding,
             icons,
         ) {
@@ -677,19 +1225,105 @@ pub fn load_speedrun_settings<'a>(
     }
 }
 
-/// Search data folder from `configuration` for speedrun with provided `name`
+/// Search data folder from `configuration` for speedrun of `game_name` and
+/// `category_name`
+///
+/// # Errors
+/// Returns an error if speedrun settings file is missing or cannot be parsed
+pub fn find_speedrun(
+    configuration: &PMLSConfiguration,
+    game_name: &str,
+    category_name: &str,
+) -> Result<SpeedrunSettings, SpeedrunSettingsFileError> {
+    find_speedrun_by_name(
+        speedrun_folder_name(game_name, category_name),
+        configuration,
+    )
+}
+
+/// Returns settings of all speedruns in data folder from `configuration`,
+/// sorted by game and category
+///
+/// # Errors
+/// Returns an error if a speedrun settings file cannot be read
+pub fn list_speedruns(
+    configuration: &PMLSConfiguration,
+) -> Result<Vec<SpeedrunSettings>, SpeedrunSettingsFileError> {
+    let data_folder = configuration.get_data_folder();
+    let mut speedruns = vec![];
+    for indexed in read_index(&data_folder)?.speedruns {
+        match read_speedrun_settings(&data_folder, indexed.folder.as_str()) {
+            Ok(ss) => speedruns.push(ss),
+            Err(e) => warn!("Skipping speedrun \"{}\": {e}", indexed.folder),
+        }
+    }
+    speedruns.sort_by_key(|ss| (ss.game_name.clone(), ss.category_name.clone()));
+    Ok(speedruns)
+}
+
+/// Search index of data folder from `configuration` for speedrun stored in
+/// folder `name`. The index is rebuilt if the speedrun is not found
 fn find_speedrun_by_name(
     name: String,
     configuration: &PMLSConfiguration,
 ) -> Result<SpeedrunSettings, SpeedrunSettingsFileError> {
-    let data_folder_path = configuration.data_folder_path.as_str();
+    let data_folder = configuration.get_data_folder();
     if name.is_empty() {
         return Err(SpeedrunSettingsFileError::UserInput(
             "Speedrun name cannot be empty.".to_string(),
         ));
     }
-    debug!("parsing {data_folder_path}");
-    for entry in WalkDir::new(data_folder_path) {
+    let is_indexed = |index: &SpeedrunIndex| {
+        index.speedruns.iter().any(|indexed| indexed.folder == name)
+            && data_folder
+                .join(name.as_str())
+                .join("settings.toml")
+                .exists()
+    };
+    if !is_indexed(&read_index(&data_folder)?) {
+        // speedrun files may have been changed by hand
+        debug!("\"{name}\" is not indexed");
+        if !is_indexed(&rebuild_index(&data_folder)?) {
+            return Err(SpeedrunSettingsFileError::Missing(name));
+        }
+    }
+    info!("Found speedrun");
+    read_speedrun_settings(&data_folder, name.as_str())
+}
+
+/// Parse settings of speedrun stored in `folder` of `data_folder`
+fn read_speedrun_settings(
+    data_folder: &Path,
+    folder: &str,
+) -> Result<SpeedrunSettings, SpeedrunSettingsFileError> {
+    trace!("Parsing speedrun settings file");
+    let mut file = File::open(data_folder.join(folder).join("settings.toml"))?;
+    let mut ss_settings = String::new();
+    file.read_to_string(&mut ss_settings)?;
+    let mut ss: SpeedrunSettings = toml::from_str(&ss_settings)?;
+    ss.data_folder = data_folder.to_path_buf();
+    Ok(ss)
+}
+
+/// Returns index of speedruns of `data_folder`. The index is rebuilt if it
+/// is missing or cannot be parsed
+fn read_index(data_folder: &Path) -> Result<SpeedrunIndex, SpeedrunSettingsFileError> {
+    let index_path = data_folder.join(INDEX_FILE_NAME);
+    if index_path.exists() {
+        match toml::from_str(fs::read_to_string(&index_path)?.as_str()) {
+            Ok(index) => return Ok(index),
+            Err(e) => warn!("Rebuilding index of speedruns that could not be parsed: {e}"),
+        }
+    }
+    rebuild_index(data_folder)
+}
+
+/// Index speedruns of `data_folder`, stored in `<game>/<category>` folders,
+/// and save the index
+fn rebuild_index(data_folder: &Path) -> Result<SpeedrunIndex, SpeedrunSettingsFileError> {
+    info!("Indexing speedruns of \"{}\"", data_folder.display());
+    let mut index = SpeedrunIndex::default();
+    for entry in WalkDir::new(data_folder).min_depth(3).max_depth(3) {
         let e = match entry {
             Ok(e) => e,
             Err(e) => {
@@ -698,21 +1332,46 @@ fn find_speedrun_by_name(
                 continue;
             }
         };
-        let ss_file = e.path().display().to_string();
-        let file_name = e.path().file_name().ok_or("could not get file name")?;
-        let ss_entry = file_name.to_str().ok_or("Error converting file name")?;
-        debug!("{ss_entry}");
-        if ss_entry == name {
-            info!("Found speedrun");
-            trace!("Parsing speedrun settings file");
-            let mut file = File::open(ss_file.as_str())?;
-            let mut ss_settings = String::new();
-            file.read_to_string(&mut ss_settings)?;
-            let ss: SpeedrunSettings = toml::from_str(&ss_settings)?;
-            return Ok(ss);
+        if !e.file_type().is_file() || e.file_name() != "settings.toml" {
+            continue;
+        }
+        let content = fs::read_to_string(e.path())?;
+        let ss = match toml::from_str::<SpeedrunSettings>(&content) {
+            Ok(ss) => ss,
+            Err(e) => {
+                warn!("Skipping speedrun settings that could not be parsed");
+                debug!("Skipped speedrun settings: {e}");
+                continue;
+            }
+        };
+        let folder = ss.get_folder_name();
+        if e.path() != data_folder.join(folder.as_str()).join("settings.toml") {
+            warn!(
+                "Skipping \"{}\": speedrun folder does not match its game and category",
+                e.path().display()
+            );
+            continue;
         }
+        index.speedruns.push(IndexedSpeedrun {
+            game_name: ss.game_name,
+            category_name: ss.category_name,
+            folder,
+        });
     }
-    Err(SpeedrunSettingsFileError::Missing(name))
+    write_index(data_folder, &mut index)?;
+    Ok(index)
+}
+
+/// Sort and save `index` of speedruns of `data_folder`
+fn write_index(
+    data_folder: &Path,
+    index: &mut SpeedrunIndex,
+) -> Result<(), SpeedrunSettingsFileError> {
+    index.speedruns.sort_by(|a, b| a.folder.cmp(&b.folder));
+    fs::create_dir_all(data_folder)?;
+    let index_content = toml::to_string(index)?;
+    write_atomically(&data_folder.join(INDEX_FILE_NAME), index_content.as_bytes())?;
+    Ok(())
 }
 
 /// Get user output. Exit program if user exits dialog
@@ -729,9 +1388,11 @@ fn get_user_output(input: &mut Input) -> Result<String, FileError> {
 
 /// Ask user for speedrun settings
 fn ask_speedrun_settings_to_user(
+    data_folder: PathBuf,
     game_name: Option<&str>,
     category_name: Option<&str>,
     split_names: Option<&str>,
+    timing_method: Option<&str>,
     keybinding: &UserKeybinding,
     icons: Option<Values>,
 ) -> Result<(SpeedrunSettings, Vec<String>), SpeedrunSettingsFileError> {
@@ -761,6 +1422,11 @@ fn ask_speedrun_settings_to_user(
         split_names = get_splits(sn.as_str());
     }
 
+    let timing_method = match timing_method {
+        Some(tm) => parse_timing_method(tm)?,
+        None => default_timing_method(),
+    };
+
     let mut icon_filepaths: Vec<String> = vec![];
     if let Some(icons) = icons {
         for icon in icons {
@@ -779,6 +1445,20 @@ fn ask_speedrun_settings_to_user(
         }
     }
 
+    let keybinding = ask_keybinding(keybinding)?;
+    let ss = SpeedrunSettings::new(
+        split_names,
+        game_name,
+        category_name,
+        keybinding,
+        timing_method,
+        data_folder,
+    )?;
+    Ok((ss, icon_filepaths))
+}
+
+/// Ask user for keys missing from `keybinding` until all keys are different
+fn ask_keybinding(keybinding: &UserKeybinding) -> Result<Keybinding, FileError> {
     loop {
         // NOTE: KeyCode does not implement display but Debug matches serialized string
         let split_key = match keybinding.split_key {
@@ -801,13 +1481,48 @@ fn ask_speedrun_settings_to_user(
             Some(k) => parse_key(k)?,
             None => ask_user_keybinding("comparison", format!("{Numpad9:?}"))?,
         };
+        let undo_split_key = match keybinding.undo_split_key {
+            Some(k) => parse_key(k)?,
+            None => ask_user_keybinding("undo split", format!("{Numpad8:?}"))?,
+        };
+        let skip_split_key = match keybinding.skip_split_key {
+            Some(k) => parse_key(k)?,
+            None => ask_user_keybinding("skip split", format!("{Numpad2:?}"))?,
+        };
+        let timing_method_key = keybinding.timing_method_key.map(parse_key).transpose()?;
+        let segment_times_key = keybinding.segment_times_key.map(parse_key).transpose()?;
+        let practice_key = keybinding.practice_key.map(parse_key).transpose()?;
+        let game_time_pause_key = keybinding.game_time_pause_key.map(parse_key).transpose()?;
 
-        let keys = vec![split_key, reset_key, pause_key, unpause_key];
-        if keys.iter().all_unique() {
-            let keybinding =
-                Keybinding::new(split_key, reset_key, pause_key, unpause_key, comparison_key);
-            let ss = SpeedrunSettings::new(split_names, game_name, category_name, keybinding)?;
-            return Ok((ss, icon_filepaths));
+        let keys = vec![
+            split_key,
+            reset_key,
+            pause_key,
+            unpause_key,
+            undo_split_key,
+            skip_split_key,
+        ];
+        if keys
+            .iter()
+            .chain(timing_method_key.iter())
+            .chain(segment_times_key.iter())
+            .chain(practice_key.iter())
+            .chain(game_time_pause_key.iter())
+            .all_unique()
+        {
+            return Ok(Keybinding::new(
+                split_key,
+                reset_key,
+                pause_key,
+                unpause_key,
+                comparison_key,
+                Some(undo_split_key),
+                Some(skip_split_key),
+                timing_method_key,
+                segment_times_key,
+                practice_key,
+                game_time_pause_key,
+            ));
         }
         warn!("No two keybinds can be the same. Retrying...");
     }
@@ -850,7 +1565,7 @@ pub fn update_configuration_with_default_speedrun(
     if auto_accept {
         let mut config = configuration;
         config.use_default_speedrun = true;
-        config.default_speedrun_name = Some(settings.get_file_name());
+        config.default_speedrun_name = Some(settings.get_folder_name());
         save_config_to_file(&config)
     } else {
         let choice =
@@ -860,7 +1575,7 @@ pub fn update_configuration_with_default_speedrun(
         if choice == dialog::Choice::Yes {
             let mut config = configuration;
             config.use_default_speedrun = true;
-            config.default_speedrun_name = Some(settings.get_file_name());
+            config.default_speedrun_name = Some(settings.get_folder_name());
             save_config_to_file(&config)
         } else {
             Ok(())
@@ -868,11 +1583,31 @@ pub fn update_configuration_with_default_speedrun(
     }
 }
 
+/// Unset default speedrun of `configuration` if it is the speedrun of
+/// `settings`
+///
+/// # Errors
+/// This functions returns an error if it cannot serialize configuration or
+/// write to file
+pub fn unset_default_speedrun(
+    configuration: PMLSConfiguration,
+    settings: &SpeedrunSettings,
+) -> Result<(), ConfigurationFileError> {
+    if configuration.default_speedrun_name != Some(settings.get_folder_name()) {
+        return Ok(());
+    }
+    let mut config = configuration;
+    config.default_speedrun_name = None;
+    save_config_to_file(&config)
+}
+
 /// Save `configuration` to file
 fn save_config_to_file(configuration: &PMLSConfiguration) -> Result<(), ConfigurationFileError> {
-    let mut file = File::create(default_config_path()?.as_str())?;
     let config_content = toml::to_string(&configuration)?;
-    file.write_all(config_content.as_bytes())?;
+    if let Some(parent) = configuration.path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomically(&configuration.path, config_content.as_bytes())?;
     info!("Configuration file created");
     Ok(())
 }
@@ -885,49 +1620,387 @@ fn save_config_to_file(configuration: &PMLSConfiguration) -> Result<(), Configur
 pub fn save_speedrun_settings_to_file(
     settings: &SpeedrunSettings,
 ) -> Result<(), SpeedrunSettingsFileError> {
-    let default_data_folder = default_data_folder()?;
-    let file_path = format!("{}/{}", default_data_folder, settings.get_file_name());
-    let mut file = File::create(file_path)?;
     let settings_content = toml::to_string(&settings)?;
-    file.write_all(settings_content.as_bytes())?;
+    fs::create_dir_all(settings.get_folder())?;
+    write_atomically(&settings.get_file_path(), settings_content.as_bytes())?;
+    let mut index = read_index(&settings.data_folder)?;
+    let folder = settings.get_folder_name();
+    if !index
+        .speedruns
+        .iter()
+        .any(|indexed| indexed.folder == folder)
+    {
+        index.speedruns.push(IndexedSpeedrun {
+            game_name: settings.game_name.clone(),
+            category_name: settings.category_name.clone(),
+            folder,
+        });
+        write_index(&settings.data_folder, &mut index)?;
+    }
 
     info!("Speedrun settings file saved");
     Ok(())
 }
 
+/// Delete folder of speedrun `settings` with all its files and remove it
+/// from the index
+///
+/// # Errors
+/// This functions returns an error if files cannot be removed
+pub fn delete_speedrun_files(settings: &SpeedrunSettings) -> Result<(), FileError> {
+    fs::remove_dir_all(settings.get_folder())?;
+    // remove game folder once its last category is deleted
+    if let Some(game_folder) = settings.get_folder().parent() {
+        if fs::read_dir(game_folder)?.next().is_none() {
+            fs::remove_dir(game_folder)?;
+        }
+    }
+    let mut index = match read_index(&settings.data_folder) {
+        Ok(index) => index,
+        Err(e) => return Err(FileError::Other(e.to_string())),
+    };
+    let folder = settings.get_folder_name();
+    index.speedruns.retain(|indexed| indexed.folder != folder);
+    if let Err(e) = write_index(&settings.data_folder, &mut index) {
+        return Err(FileError::Other(e.to_string()));
+    }
+    info!("Deleted speedrun files");
+    Ok(())
+}
+
+/// Change keybindings and timing method of speedrun `settings` with values
+/// provided by the user. Other settings are kept
+///
+/// # Errors
+/// Returns an error if provided values are invalid or keys are not unique
+pub fn edit_speedrun_settings(
+    settings: SpeedrunSettings,
+    user_keybinding: &UserKeybinding,
+    timing_method: Option<&str>,
+) -> Result<SpeedrunSettings, SpeedrunSettingsFileError> {
+    let current = settings.keybindings;
+    let parse_or = |key: Option<&str>, current: KeyCode| key.map_or(Ok(current), parse_key);
+    let parse_optional_or = |key: Option<&str>, current: Option<KeyCode>| match key {
+        Some(k) => parse_key(k).map(Some),
+        None => Ok(current),
+    };
+    let keybindings = Keybinding::new(
+        parse_or(user_keybinding.split_key, current.split_key)?,
+        parse_or(user_keybinding.reset_key, current.reset_key)?,
+        parse_or(user_keybinding.pause_key, current.pause_key)?,
+        parse_or(user_keybinding.unpause_key, current.unpause_key)?,
+        parse_or(user_keybinding.comparison_key, current.comparison_key)?,
+        parse_optional_or(user_keybinding.undo_split_key, current.undo_split_key)?,
+        parse_optional_or(user_keybinding.skip_split_key, current.skip_split_key)?,
+        parse_optional_or(user_keybinding.timing_method_key, current.timing_method_key)?,
+        parse_optional_or(user_keybinding.segment_times_key, current.segment_times_key)?,
+        parse_optional_or(user_keybinding.practice_key, current.practice_key)?,
+        parse_optional_or(
+            user_keybinding.game_time_pause_key,
+            current.game_time_pause_key,
+        )?,
+    );
+    let timing_method = match timing_method {
+        Some(t) => parse_timing_method(t)?,
+        None => settings.timing_method,
+    };
+    let mut edited = SpeedrunSettings::new(
+        settings.split_names,
+        settings.game_name,
+        settings.category_name,
+        keybindings,
+        timing_method,
+        settings.data_folder,
+    )?;
+    edited.layout_path = settings.layout_path;
+    edited.auto_splitter_path = settings.auto_splitter_path;
+    edited.summary_rows = settings.summary_rows;
+    edited.delta_colors = settings.delta_colors;
+    edited.time_format = settings.time_format;
+    edited.reset_policy = settings.reset_policy;
+    edited.run_backups = settings.run_backups;
+    edited.split_icons = settings.split_icons;
+    Ok(edited)
+}
+
 /// Save `run` to file that corresponds to speedrun `settings`
 ///
 /// # Errors
 /// This functions returns an error if it cannot serialize configuration or
 /// write to file
 pub fn save_run_to_file(run: &Run, settings: &SpeedrunSettings) -> Result<(), RunFileError> {
-    let default_data_folder = default_data_folder()?;
-    let file_path = format!("{default_data_folder}/{}", settings.get_run_file_name());
-    let file = File::create(file_path)?;
-    let writer = BufWriter::new(file);
-    livesplit::save_run(run, writer)?;
+    let mut run_content = String::new();
+    livesplit::save_run(run, &mut run_content)?;
+    backup_run_file_once(settings)?;
+    write_atomically(&settings.get_run_file_path(), run_content.as_bytes())?;
+    Ok(())
+}
+
+/// Number of temporary files created since launch
+static TEMPORARY_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Replace file at `path` with `content`. Content is written to a temporary
+/// file first, so an interrupted write never truncates the file
+pub(crate) fn write_atomically(path: &Path, content: &[u8]) -> Result<(), std::io::Error> {
+    // NOTE: each write has its own temporary file, so that concurrent saves
+    //       (autosave and closing the window) do not write into the same one
+    let count = TEMPORARY_FILE_COUNT.fetch_add(1, Ordering::Relaxed);
+    let mut temporary_path = path.as_os_str().to_owned();
+    temporary_path.push(format!(".{}-{count}.tmp", std::process::id()));
+    let temporary_path = PathBuf::from(temporary_path);
+    let mut file = File::create(&temporary_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    fs::rename(&temporary_path, path)
+}
+
+/// Run files backed up since launch
+static BACKED_UP_RUN_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Back up run file of speedrun `settings` the first time it is saved since
+/// launch, so saving every attempt does not rotate out older backups
+fn backup_run_file_once(settings: &SpeedrunSettings) -> Result<(), FileError> {
+    let run_file_path = settings.get_run_file_path();
+    // NOTE: a poisoned list only means a backup failed
+    let mut backed_up = BACKED_UP_RUN_FILES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if backed_up.contains(&run_file_path) {
+        return Ok(());
+    }
+    backup_run_file(settings)?;
+    rotate_run_backups(settings)?;
+    backed_up.push(run_file_path);
     Ok(())
 }
 
+/// Copy run file of speedrun `settings` to the backups folder. Nothing is done
+/// if the run file does not exist yet or did not change since the newest
+/// backup
+fn backup_run_file(settings: &SpeedrunSettings) -> Result<(), FileError> {
+    let run_file_path = settings.get_run_file_path();
+    if settings.run_backups == 0 || !run_file_path.exists() {
+        return Ok(());
+    }
+    let run_content = fs::read(&run_file_path)?;
+    if let Some((_, newest_backup)) = list_run_backups(settings)?.last() {
+        if fs::read(newest_backup).is_ok_and(|backup| backup == run_content) {
+            return Ok(());
+        }
+    }
+    let backups_folder = settings.get_backups_folder();
+    fs::create_dir_all(&backups_folder)?;
+    let now = AtomicDateTime::now().time;
+    let timestamp = format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}",
+        now.year(),
+        u8::from(now.month()),
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second(),
+        now.millisecond()
+    );
+    fs::write(backups_folder.join(format!("{timestamp}.lss")), run_content)?;
+    Ok(())
+}
+
+/// Remove the oldest backups of run file of speedrun `settings` past the
+/// number of kept backups
+fn rotate_run_backups(settings: &SpeedrunSettings) -> Result<(), FileError> {
+    if settings.run_backups == 0 {
+        return Ok(());
+    }
+    let backups = list_run_backups(settings)?;
+    for (_, backup) in backups
+        .iter()
+        .take(backups.len().saturating_sub(settings.run_backups))
+    {
+        fs::remove_file(backup)?;
+    }
+    Ok(())
+}
+
+/// Returns timestamp and path of backups of run file of speedrun `settings`,
+/// oldest first
+///
+/// # Errors
+/// Returns an error if the backups folder cannot be read
+pub fn list_run_backups(settings: &SpeedrunSettings) -> Result<Vec<(String, PathBuf)>, FileError> {
+    let backups_folder = settings.get_backups_folder();
+    if !backups_folder.exists() {
+        return Ok(vec![]);
+    }
+    let mut backups = vec![];
+    for entry in fs::read_dir(backups_folder)? {
+        let path = entry?.path();
+        let timestamp = path
+            .file_name()
+            .and_then(std::ffi::OsStr::to_str)
+            .and_then(parse_backup_timestamp)
+            .map(str::to_string);
+        if let Some(timestamp) = timestamp {
+            backups.push((timestamp, path));
+        }
+    }
+    // timestamps sort chronologically
+    backups.sort();
+    Ok(backups)
+}
+
+/// Replace run file of speedrun `settings` with its backup taken at
+/// `timestamp` (the most recent one if missing). The replaced run file is
+/// backed up first, without removing old backups. Returns path of restored
+/// backup
+///
+/// # Errors
+/// Returns an error if no backup matches or if the backup cannot be parsed
+pub fn restore_run_backup(
+    settings: &SpeedrunSettings,
+    timestamp: Option<&str>,
+) -> Result<PathBuf, RunFileError> {
+    let backups = list_run_backups(settings)?;
+    let backup = match timestamp {
+        Some(timestamp) => backups.into_iter().find(|(t, _)| t == timestamp),
+        None => backups.into_iter().last(),
+    };
+    let Some((_, backup)) = backup else {
+        return Err(RunFileError::UserInput(format!(
+            "No backup{} of run file \"{}\"",
+            timestamp.map_or(String::new(), |t| format!(" taken at \"{t}\"")),
+            settings.get_run_file_path().display()
+        )));
+    };
+    // refuse to restore a corrupted backup
+    let run_content = fs::read(&backup)?;
+    composite::parse(&run_content, None)?;
+    backup_run_file(settings)?;
+    write_atomically(&settings.get_run_file_path(), &run_content)?;
+    info!("Restored run file from \"{}\"", backup.display());
+    Ok(backup)
+}
+
+/// Returns path of segment practice log of speedrun `settings`
+#[must_use]
+pub fn practice_log_path(settings: &SpeedrunSettings) -> PathBuf {
+    settings.get_folder().join("practice.log")
+}
+
+/// Returns path of journal of attempt in progress of speedrun `settings`
+#[must_use]
+pub fn journal_path(settings: &SpeedrunSettings) -> PathBuf {
+    settings.get_folder().join("journal.json")
+}
+
+/// Convert image at `source` to PNG, resized to fit in `SPLIT_ICON_SIZE`, and
+/// store it in the icons folder of speedrun `settings` as icon of split `i`.
+/// Speedrun settings need to be saved afterwards
+///
+/// # Errors
+/// Returns an error if split `i` does not exist or the image cannot be
+/// converted
+pub fn import_split_icon(
+    settings: &mut SpeedrunSettings,
+    i: usize,
+    source: &Path,
+) -> Result<(), SpeedrunSettingsFileError> {
+    store_split_icon(settings, i, &image::open(source)?)?;
+    info!(
+        "Imported icon of split \"{}\" from \"{}\"",
+        settings.split_names[i],
+        source.display()
+    );
+    Ok(())
+}
+
+/// Store `icon`, resized to fit in `SPLIT_ICON_SIZE`, in the icons folder of
+/// speedrun `settings` as icon of split `i`
+fn store_split_icon(
+    settings: &mut SpeedrunSettings,
+    i: usize,
+    icon: &DynamicImage,
+) -> Result<(), SpeedrunSettingsFileError> {
+    if i >= settings.split_names.len() {
+        return Err(SpeedrunSettingsFileError::UserInput(format!(
+            "No split number {}",
+            i + 1
+        )));
+    }
+    // keep pixel art sharp when enlarging
+    let filter = if icon.width() < SPLIT_ICON_SIZE && icon.height() < SPLIT_ICON_SIZE {
+        FilterType::Nearest
+    } else {
+        FilterType::Lanczos3
+    };
+    let icon = icon.resize(SPLIT_ICON_SIZE, SPLIT_ICON_SIZE, filter);
+    let mut icon_content = Cursor::new(vec![]);
+    icon.write_to(&mut icon_content, ImageOutputFormat::Png)?;
+    let file_name = SpeedrunSettings::get_split_icon_file_name(i);
+    let icon_path = settings.get_folder().join(file_name.as_str());
+    if let Some(icons_folder) = icon_path.parent() {
+        fs::create_dir_all(icons_folder)?;
+    }
+    write_atomically(&icon_path, icon_content.get_ref())?;
+    settings
+        .split_icons
+        .resize(settings.split_names.len(), String::new());
+    settings.split_icons[i] = file_name;
+    Ok(())
+}
+
+/// Remove icon of split `i` from the icons folder of speedrun `settings`.
+/// Speedrun settings need to be saved afterwards
+///
+/// # Errors
+/// Returns an error if the icon cannot be removed
+pub fn remove_split_icon(
+    settings: &mut SpeedrunSettings,
+    i: usize,
+) -> Result<(), SpeedrunSettingsFileError> {
+    let Some(icon) = settings.split_icons.get(i) else {
+        return Ok(());
+    };
+    if let Some(icon_path) = settings.get_split_icon_path(icon) {
+        if icon_path.exists() {
+            fs::remove_file(icon_path)?;
+        }
+    }
+    settings.split_icons[i].clear();
+    info!("Removed icon of split \"{}\"", settings.split_names[i]);
+    Ok(())
+}
+
+/// Set icons of segments of `run` to split icons of speedrun `settings`.
+/// Segments of splits without icon, or whose icon cannot be read, keep their
+/// icon
+pub fn load_split_icons(settings: &SpeedrunSettings, run: &mut Run) {
+    for (i, icon) in settings.split_icons.iter().enumerate().take(run.len()) {
+        if icon.is_empty() {
+            continue;
+        }
+        let Some(icon_path) = settings.get_split_icon_path(icon) else {
+            warn!("Skipping icon \"{icon}\" outside of speedrun folder");
+            continue;
+        };
+        match Image::from_file(&icon_path, &mut vec![]) {
+            Ok(image) => run.segment_mut(i).set_icon(image),
+            Err(e) => warn!("Skipping icon \"{}\": {e}", icon_path.display()),
+        }
+    }
+}
+
 /// Parse run from data folder present in `settings`
 ///
 /// # Errors
 /// Returns an error if run file could not be parsed
 pub fn parse_run_from_file(settings: &SpeedrunSettings) -> Result<Run, RunFileError> {
     // Load the file.
-    let default_data_folder = default_data_folder()?;
-    let file_path = PathBuf::from(format!(
-        "{default_data_folder}/{}",
-        settings.get_run_file_name()
-    ));
-    let file = File::open(&file_path)?;
-    let file = BufReader::new(file);
-
-    // We want to load additional files from the file system, like segment icons.
-    let load_files = true;
+    let file_path = settings.get_run_file_path();
+    let content = fs::read(&file_path)?;
 
-    // Actually parse the file.
-    let parsed = composite::parse(file, Some(file_path), load_files)?;
+    // Actually parse the file. Giving its path loads additional files from
+    // the file system, like segment icons.
+    let parsed = composite::parse(&content, Some(&file_path))?;
 
     // Print out the detected file format.
     info!("Splits File Format: {}", parsed.kind);
@@ -937,6 +2010,122 @@ pub fn parse_run_from_file(settings: &SpeedrunSettings) -> Result<Run, RunFileEr
     Ok(run)
 }
 
+/// Create speedrun in data folder from `configuration` with splits file at
+/// `path`, in any format supported by livesplit-core (livesplit, wsplit,
+/// llanfair, splits.io...). Game and category names of the file are replaced
+/// by `game_name` and `category_name` if provided. Keys missing from
+/// `user_keybinding` are asked to the user. Split icons of the file are copied
+/// into the speedrun folder. Returns settings and run, which need to be saved
+///
+/// # Errors
+/// Returns an error if the file cannot be parsed, the speedrun already exists
+/// or provided values are invalid
+pub fn import_speedrun(
+    configuration: &PMLSConfiguration,
+    path: &Path,
+    game_name: Option<&str>,
+    category_name: Option<&str>,
+    timing_method: Option<&str>,
+    user_keybinding: &UserKeybinding,
+) -> Result<(SpeedrunSettings, Run), SpeedrunSettingsFileError> {
+    let content = fs::read(path)?;
+    let parsed = match composite::parse(&content, Some(path)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Err(SpeedrunSettingsFileError::UserInput(format!(
+                "Cannot parse splits file \"{}\": {e}",
+                path.display()
+            )))
+        }
+    };
+    info!("Splits File Format: {}", parsed.kind);
+    let mut run = parsed.run;
+    let game_name = game_name.unwrap_or(run.game_name()).trim().to_string();
+    let category_name = category_name
+        .unwrap_or(run.category_name())
+        .trim()
+        .to_string();
+    if game_name.is_empty() || category_name.is_empty() {
+        return Err(SpeedrunSettingsFileError::UserInput(
+            "Splits file has no game or category name, provide them with --game and --category"
+                .to_string(),
+        ));
+    }
+    if run.is_empty() {
+        return Err(SpeedrunSettingsFileError::UserInput(format!(
+            "Splits file \"{}\" has no splits",
+            path.display()
+        )));
+    }
+    if find_speedrun(configuration, game_name.as_str(), category_name.as_str()).is_ok() {
+        return Err(SpeedrunSettingsFileError::UserInput(format!(
+            "Speedrun \"{game_name}: {category_name}\" already exists"
+        )));
+    }
+    run.set_game_name(game_name.as_str());
+    run.set_category_name(category_name.as_str());
+    let split_names = run
+        .segments()
+        .iter()
+        .map(|segment| segment.name().to_string())
+        .collect();
+    let timing_method = match timing_method {
+        Some(tm) => parse_timing_method(tm)?,
+        None => default_timing_method(),
+    };
+    let keybinding = ask_keybinding(user_keybinding)?;
+    let mut settings = SpeedrunSettings::new(
+        split_names,
+        game_name,
+        category_name,
+        keybinding,
+        timing_method,
+        configuration.get_data_folder(),
+    )?;
+    for (i, segment) in run.segments().iter().enumerate() {
+        let icon = segment.icon().data();
+        if icon.is_empty() {
+            continue;
+        }
+        match image::load_from_memory(icon) {
+            Ok(icon) => store_split_icon(&mut settings, i, &icon)?,
+            Err(e) => warn!("Skipping icon of split \"{}\": {e}", segment.name()),
+        }
+    }
+    info!("Imported speedrun from \"{}\"", path.display());
+    Ok((settings, run))
+}
+
+/// Parse layout referenced by speedrun `settings`. Returns `None` if
+/// speedrun does not use a layout
+///
+/// Both original livesplit layouts (`.lsl`) and livesplit-core layouts
+/// (`.ls1l`) are supported.
+///
+/// # Errors
+/// Returns an error if layout file could not be read or parsed
+pub fn parse_layout_from_file(
+    settings: &SpeedrunSettings,
+) -> Result<Option<Layout>, LayoutFileError> {
+    let layout_path = match &settings.layout_path {
+        Some(p) => PathBuf::from(p),
+        None => return Ok(None),
+    };
+    let layout = match layout_path.extension().and_then(std::ffi::OsStr::to_str) {
+        Some("lsl") => layout::parser::parse(&fs::read_to_string(&layout_path)?)?,
+        Some("ls1l") => Layout::from_settings(LayoutSettings::from_json(BufReader::new(
+            File::open(&layout_path)?,
+        ))?),
+        _ => {
+            return Err(LayoutFileError::UnknownFormat(
+                layout_path.display().to_string(),
+            ))
+        }
+    };
+    info!("Loaded layout {}", layout_path.display());
+    Ok(Some(layout))
+}
+
 /// Ask user for icon
 fn ask_for_icons(icon_name: &str) -> Result<String, SpeedrunSettingsFileError> {
     let img_filepath = dialog::FileSelection::new("")
@@ -951,6 +2140,175 @@ fn ask_for_icons(icon_name: &str) -> Result<String, SpeedrunSettingsFileError> {
     }
 }
 
+/// Returns folder of files of speedrun `game_name` `category_name`,
+/// relative to the data folder: `GAME/CATEGORY` with both names escaped
+fn speedrun_folder_name(game_name: &str, category_name: &str) -> String {
+    format!(
+        "{}/{}",
+        escape_file_name(game_name),
+        escape_file_name(category_name)
+    )
+}
+
+/// Returns timestamp of run file backup named `file_name`, if it is one
+fn parse_backup_timestamp(file_name: &str) -> Option<&str> {
+    let timestamp = file_name.strip_suffix(".lss")?;
+    let is_timestamp = timestamp.len() == "YYYYMMDD-hhmmss-mmm".len()
+        && timestamp.chars().all(|c| c.is_ascii_digit() || c == '-');
+    is_timestamp.then_some(timestamp)
+}
+
+/// Escape `name` so it can be used in a file name. Path separators, dots (no
+/// `..` or hidden files), underscores (separator of game and category names in
+/// flat file names), control characters and `%` are replaced by `%XX` for each
+/// of their bytes
+fn escape_file_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '%' | '/' | '\\' | '.' | '_') || c.is_control() {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                escaped.push_str(format!("%{byte:02X}").as_str());
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Move files of speedruns stored flat in the data folder of `configuration`
+/// (`GAME_CATEGORY.txt`, `GAME_CATEGORY.lss`...) to their own `GAME/CATEGORY`
+/// folder, then index speedruns. Default speedrun of `configuration` is
+/// updated accordingly
+///
+/// # Errors
+/// Returns an error if files cannot be moved or configuration cannot be
+/// saved
+fn migrate_speedrun_files(
+    configuration: &mut PMLSConfiguration,
+) -> Result<(), ConfigurationFileError> {
+    let data_folder = configuration.get_data_folder();
+    let legacy_backups_folder = data_folder.join("backups");
+    let settings_files = WalkDir::new(&data_folder)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|e| e == "txt"))
+        .filter_map(|e| {
+            let content = fs::read_to_string(e.path()).ok()?;
+            let ss = toml::from_str::<SpeedrunSettings>(&content).ok()?;
+            Some((e.into_path(), ss))
+        })
+        .collect::<Vec<_>>();
+    for (settings_path, mut ss) in settings_files {
+        ss.data_folder.clone_from(&data_folder);
+        let folder = ss.get_folder();
+        info!(
+            "Moving files of speedrun \"{}\" \"{}\" to \"{}\"",
+            ss.game_name,
+            ss.category_name,
+            folder.display()
+        );
+        let mut moved_files = vec![(settings_path, ss.get_file_path())];
+        // files were named with raw names, then with escaped names
+        let legacy_stems = [
+            format!("{}_{}", ss.game_name, ss.category_name),
+            format!(
+                "{}_{}",
+                escape_file_name(ss.game_name.as_str()),
+                escape_file_name(ss.category_name.as_str())
+            ),
+        ];
+        for legacy_stem in &legacy_stems {
+            moved_files.push((
+                data_folder.join(format!("{legacy_stem}.lss")),
+                ss.get_run_file_path(),
+            ));
+            moved_files.push((
+                data_folder.join(format!("{legacy_stem}_practice.log")),
+                practice_log_path(&ss),
+            ));
+            moved_files.push((
+                data_folder.join(format!("{legacy_stem}_journal.json")),
+                journal_path(&ss),
+            ));
+            if !legacy_backups_folder.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&legacy_backups_folder)? {
+                let path = entry?.path();
+                let timestamp = path
+                    .file_name()
+                    .and_then(std::ffi::OsStr::to_str)
+                    .and_then(|name| name.strip_prefix(legacy_stem.as_str()))
+                    .and_then(|name| name.strip_prefix('_'))
+                    .and_then(parse_backup_timestamp)
+                    .map(str::to_string);
+                if let Some(timestamp) = timestamp {
+                    let backup = ss.get_backups_folder().join(format!("{timestamp}.lss"));
+                    moved_files.push((path, backup));
+                }
+            }
+        }
+        for (from, to) in moved_files {
+            if !from.is_file() {
+                continue;
+            }
+            if to.exists() {
+                warn!(
+                    "Not moving \"{}\": \"{}\" already exists",
+                    from.display(),
+                    to.display()
+                );
+                continue;
+            }
+            debug!("Moving \"{}\" to \"{}\"", from.display(), to.display());
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(from, to)?;
+        }
+        let is_default = legacy_stems.iter().any(|legacy_stem| {
+            configuration.default_speedrun_name == Some(format!("{legacy_stem}.txt"))
+        });
+        if is_default {
+            configuration.default_speedrun_name = Some(ss.get_folder_name());
+            save_config_to_file(configuration)?;
+        }
+    }
+    if legacy_backups_folder.is_dir() && fs::read_dir(&legacy_backups_folder)?.next().is_none() {
+        fs::remove_dir(&legacy_backups_folder)?;
+    }
+    if let Err(e) = rebuild_index(&data_folder) {
+        return Err(ConfigurationFileError::Other(e.to_string()));
+    }
+    Ok(())
+}
+
+/// Width and height split icons are resized to fit in
+const SPLIT_ICON_SIZE: u32 = 64;
+
+/// Number of run file backups kept when not configured
+fn default_run_backups() -> usize {
+    10
+}
+
+/// Timing method of speedruns created without one
+fn default_timing_method() -> TimingMethod {
+    TimingMethod::RealTime
+}
+
+/// Parse `timing_method` ("real" or "game")
+fn parse_timing_method(timing_method: &str) -> Result<TimingMethod, FileError> {
+    match timing_method {
+        "real" => Ok(TimingMethod::RealTime),
+        "game" => Ok(TimingMethod::GameTime),
+        _ => Err(FileError::UserInput(format!(
+            "Unknown timing method \"{timing_method}\" (possible values: real, game)"
+        ))),
+    }
+}
+
 /// Parse `key`
 fn parse_key(key: &str) -> Result<KeyCode, FileError> {
     Ok(key.parse::<KeyCode>()?)
@@ -961,3 +2319,632 @@ impl<'a> From<()> for FileError {
         FileError::Other("Could not convert key".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns settings of a speedrun with 3 splits stored in an empty data
+    /// folder named after `test_name`
+    fn speedrun_settings(test_name: &str) -> SpeedrunSettings {
+        let data_folder =
+            std::env::temp_dir().join(format!("pmls-test-{}-{test_name}", std::process::id()));
+        if data_folder.exists() {
+            fs::remove_dir_all(&data_folder).unwrap();
+        }
+        fs::create_dir_all(&data_folder).unwrap();
+        let keybindings = Keybinding::new(
+            KeyCode::Numpad1,
+            KeyCode::Numpad3,
+            KeyCode::Numpad5,
+            KeyCode::Numpad7,
+            KeyCode::Numpad9,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        SpeedrunSettings::new(
+            vec!["Intro".to_string(), "Boss".to_string(), "End".to_string()],
+            "Game".to_string(),
+            "Any%".to_string(),
+            keybindings,
+            TimingMethod::RealTime,
+            data_folder,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn speedrun_without_layout_has_no_layout() {
+        let settings = speedrun_settings("no-layout");
+        assert!(matches!(parse_layout_from_file(&settings), Ok(None)));
+    }
+
+    #[test]
+    fn layout_with_unknown_extension_is_rejected() {
+        let mut settings = speedrun_settings("unknown-layout");
+        let layout_path = settings.data_folder.join("layout.txt");
+        fs::write(&layout_path, "").unwrap();
+        settings
+            .set_layout_path(&layout_path.display().to_string())
+            .unwrap();
+        assert!(matches!(
+            parse_layout_from_file(&settings),
+            Err(LayoutFileError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn missing_layout_is_rejected() {
+        let mut settings = speedrun_settings("missing-layout");
+        let layout_path = settings.data_folder.join("layout.ls1l");
+        assert!(settings
+            .set_layout_path(&layout_path.display().to_string())
+            .is_err());
+        assert!(settings.get_layout_path().is_none());
+        assert!(settings
+            .set_layout_path(&settings.data_folder.display().to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn auto_splitter_path_is_absolute() {
+        let mut settings = speedrun_settings("auto-splitter-path");
+        let auto_splitter_path = settings.data_folder.join("splitter.wasm");
+        assert!(settings
+            .set_auto_splitter_path(&auto_splitter_path.display().to_string())
+            .is_err());
+        assert!(settings.get_auto_splitter_path().is_none());
+
+        fs::write(&auto_splitter_path, "").unwrap();
+        let relative_path = settings.data_folder.join(".").join("splitter.wasm");
+        settings
+            .set_auto_splitter_path(&relative_path.display().to_string())
+            .unwrap();
+        assert_eq!(
+            settings.get_auto_splitter_path().map(PathBuf::from),
+            Some(fs::canonicalize(&auto_splitter_path).unwrap())
+        );
+    }
+
+    #[test]
+    fn livesplit_core_layout_is_parsed() {
+        let mut settings = speedrun_settings("ls1l-layout");
+        let layout_path = settings.data_folder.join("layout.ls1l");
+        Layout::default_layout()
+            .settings()
+            .write_json(File::create(&layout_path).unwrap())
+            .unwrap();
+        settings
+            .set_layout_path(&layout_path.display().to_string())
+            .unwrap();
+        assert!(matches!(parse_layout_from_file(&settings), Ok(Some(_))));
+    }
+
+    /// Returns configuration using data folder of speedrun `settings`
+    fn configuration(settings: &SpeedrunSettings) -> PMLSConfiguration {
+        PMLSConfiguration::new(
+            &settings.data_folder.join("config.toml"),
+            Some(&settings.data_folder),
+        )
+        .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    #[test]
+    fn saved_speedruns_are_listed_found_and_deleted() {
+        let settings = speedrun_settings("list-find-delete");
+        let configuration = configuration(&settings);
+        assert!(list_speedruns(&configuration).unwrap().is_empty());
+
+        save_speedrun_settings_to_file(&settings).unwrap();
+
+        let speedruns = list_speedruns(&configuration).unwrap();
+        assert_eq!(speedruns.len(), 1);
+        assert_eq!(speedruns[0].get_split_names(), settings.get_split_names());
+        let found = find_speedrun(&configuration, "Game", "Any%").unwrap();
+        assert_eq!(found.get_category_name(), "Any%");
+        assert!(find_speedrun(&configuration, "Game", "100%").is_err());
+
+        delete_speedrun_files(&settings).unwrap();
+
+        assert!(list_speedruns(&configuration).unwrap().is_empty());
+        assert!(!settings.data_folder.join("Game").exists());
+    }
+
+    #[test]
+    fn keys_must_be_unique() {
+        let settings = speedrun_settings("duplicate-keys");
+        let keybindings = Keybinding::new(
+            KeyCode::Numpad1,
+            KeyCode::Numpad1,
+            KeyCode::Numpad5,
+            KeyCode::Numpad7,
+            KeyCode::Numpad9,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(SpeedrunSettings::new(
+            settings.get_split_names(),
+            settings.get_game_name(),
+            settings.get_category_name(),
+            keybindings,
+            TimingMethod::RealTime,
+            settings.data_folder,
+        )
+        .is_err());
+    }
+
+    /// Returns run of speedrun `settings` named `category_name`
+    fn run_named(settings: &SpeedrunSettings, category_name: &str) -> Run {
+        let mut run = Run::new();
+        run.set_category_name(category_name);
+        for name in settings.get_split_names() {
+            run.push_segment(livesplit_core::Segment::new(name));
+        }
+        run
+    }
+
+    /// Returns category name of run file of speedrun `settings`
+    fn saved_category_name(settings: &SpeedrunSettings) -> String {
+        let run_content = fs::read(settings.get_run_file_path()).unwrap();
+        let run = composite::parse(&run_content, None).unwrap().run;
+        run.category_name().to_string()
+    }
+
+    #[test]
+    fn backup_timestamps_are_parsed() {
+        assert_eq!(
+            parse_backup_timestamp("20240102-030405-006.lss"),
+            Some("20240102-030405-006")
+        );
+        assert_eq!(parse_backup_timestamp("20240102-030405-006.txt"), None);
+        assert_eq!(parse_backup_timestamp("20240102-030405.lss"), None);
+        assert_eq!(parse_backup_timestamp("2024010a-030405-006.lss"), None);
+        assert_eq!(parse_backup_timestamp("Game_Any%.lss"), None);
+    }
+
+    #[test]
+    fn concurrent_writes_do_not_collide() {
+        let settings = speedrun_settings("concurrent-writes");
+        let path = settings.get_run_file_path();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || write_atomically(&path, format!("{i}").as_bytes()))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.parse::<usize>().is_ok_and(|i| i < 8), "{content}");
+        let files = fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(files, 1);
+    }
+
+    #[test]
+    fn run_file_is_backed_up_once_per_launch() {
+        let settings = speedrun_settings("backup-once");
+        fs::create_dir_all(settings.get_folder()).unwrap();
+        let mut previous_content = String::new();
+        livesplit::save_run(&run_named(&settings, "previous"), &mut previous_content).unwrap();
+        write_atomically(&settings.get_run_file_path(), previous_content.as_bytes()).unwrap();
+
+        for category_name in ["first", "second", "third"] {
+            assert!(save_run_to_file(&run_named(&settings, category_name), &settings).is_ok());
+        }
+
+        let backups = list_run_backups(&settings).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(fs::read_to_string(&backups[0].1).unwrap(), previous_content);
+        assert_eq!(saved_category_name(&settings), "third");
+    }
+
+    #[test]
+    fn oldest_backups_are_rotated_out() {
+        let mut settings = speedrun_settings("rotate-backups");
+        settings.run_backups = 2;
+        let backups_folder = settings.get_backups_folder();
+        fs::create_dir_all(&backups_folder).unwrap();
+        for timestamp in [
+            "20240101-000000-000",
+            "20240102-000000-000",
+            "20240103-000000-000",
+        ] {
+            fs::write(backups_folder.join(format!("{timestamp}.lss")), "").unwrap();
+        }
+        fs::write(backups_folder.join("notes.txt"), "").unwrap();
+
+        rotate_run_backups(&settings).unwrap();
+
+        let timestamps: Vec<String> = list_run_backups(&settings)
+            .unwrap()
+            .into_iter()
+            .map(|(timestamp, _)| timestamp)
+            .collect();
+        assert_eq!(timestamps, ["20240102-000000-000", "20240103-000000-000"]);
+        assert!(backups_folder.join("notes.txt").exists());
+    }
+
+    #[test]
+    fn restoring_a_backup_backs_up_the_run_file() {
+        let settings = speedrun_settings("restore-backup");
+        let backups_folder = settings.get_backups_folder();
+        fs::create_dir_all(&backups_folder).unwrap();
+        let mut backup_content = String::new();
+        livesplit::save_run(&run_named(&settings, "backup"), &mut backup_content).unwrap();
+        fs::write(
+            backups_folder.join("20240101-000000-000.lss"),
+            backup_content,
+        )
+        .unwrap();
+        fs::write(backups_folder.join("20240102-000000-000.lss"), "corrupted").unwrap();
+        assert!(save_run_to_file(&run_named(&settings, "current"), &settings).is_ok());
+
+        assert!(restore_run_backup(&settings, None).is_err());
+        assert!(restore_run_backup(&settings, Some("20240101-000000-000")).is_ok());
+
+        assert_eq!(saved_category_name(&settings), "backup");
+        let (_, newest_backup) = list_run_backups(&settings).unwrap().pop().unwrap();
+        let newest_backup = fs::read(newest_backup).unwrap();
+        let newest_backup = composite::parse(&newest_backup, None).unwrap();
+        assert_eq!(newest_backup.run.category_name(), "current");
+    }
+
+    #[test]
+    fn base_directory_falls_back_to_home() {
+        // NOTE: variable only used by this test, tests run in parallel
+        let variable = "PMLS_TEST_BASE_DIRECTORY";
+        let fallback = Path::new(&std::env::var("HOME").unwrap()).join(".local/share");
+
+        std::env::remove_var(variable);
+        assert_eq!(base_directory(variable, ".local/share").unwrap(), fallback);
+        std::env::set_var(variable, "relative/data");
+        assert_eq!(base_directory(variable, ".local/share").unwrap(), fallback);
+        std::env::set_var(variable, "/srv/data");
+        assert_eq!(
+            base_directory(variable, ".local/share").unwrap(),
+            Path::new("/srv/data")
+        );
+        std::env::remove_var(variable);
+    }
+
+    /// Writes legacy configuration using `legacy_data_folder` and a speedrun
+    /// into it, in a folder named after `test_name`. Returns the folder
+    fn write_legacy_installation(test_name: &str) -> PathBuf {
+        let settings = speedrun_settings(test_name);
+        let legacy_data_folder = settings.data_folder.join(".pmls");
+        fs::create_dir_all(legacy_data_folder.join("Game")).unwrap();
+        let mut config = configuration(&settings);
+        config.data_folder_path = legacy_data_folder.display().to_string();
+        fs::write(
+            settings.data_folder.join("legacy.toml"),
+            toml::to_string(&config).unwrap(),
+        )
+        .unwrap();
+        settings.data_folder
+    }
+
+    /// Returns data folder path of configuration at `path`
+    fn configured_data_folder(path: &Path) -> String {
+        let config: PMLSConfiguration = toml::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        config.data_folder_path
+    }
+
+    #[test]
+    fn legacy_files_are_migrated() {
+        let folder = write_legacy_installation("migrate-legacy");
+        let data_folder = folder.join("share/pmls");
+
+        migrate_files(
+            &folder.join("legacy.toml"),
+            &folder.join(".pmls"),
+            folder.join("config/config.toml"),
+            &data_folder,
+        )
+        .unwrap_or_else(|e| panic!("{e}"));
+
+        assert!(data_folder.join("Game").is_dir());
+        assert!(!folder.join(".pmls").exists());
+        assert!(!folder.join("legacy.toml").exists());
+        assert_eq!(
+            configured_data_folder(&folder.join("config/config.toml")),
+            data_folder.display().to_string()
+        );
+    }
+
+    #[test]
+    fn legacy_data_folder_is_kept_when_target_exists() {
+        let folder = write_legacy_installation("migrate-existing-target");
+        let data_folder = folder.join("share/pmls");
+        fs::create_dir_all(&data_folder).unwrap();
+
+        migrate_files(
+            &folder.join("legacy.toml"),
+            &folder.join(".pmls"),
+            folder.join("config/config.toml"),
+            &data_folder,
+        )
+        .unwrap_or_else(|e| panic!("{e}"));
+
+        assert!(folder.join(".pmls/Game").is_dir());
+        assert!(!data_folder.join("Game").exists());
+        assert_eq!(
+            configured_data_folder(&folder.join("config/config.toml")),
+            folder.join(".pmls").display().to_string()
+        );
+    }
+
+    /// Returns `escaped` file name with `%XX` bytes decoded
+    fn unescape_file_name(escaped: &str) -> String {
+        let mut bytes = vec![];
+        let mut rest = escaped.as_bytes();
+        while let Some((&byte, tail)) = rest.split_first() {
+            if byte == b'%' {
+                let hex = std::str::from_utf8(&tail[..2]).unwrap();
+                bytes.push(u8::from_str_radix(hex, 16).unwrap());
+                rest = &tail[2..];
+            } else {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn escaped_file_names_round_trip() {
+        for name in [
+            "Super Mario 64",
+            "Any% (No LBLJ)",
+            "AC/DC: Live",
+            "C:\\Games",
+            "..",
+            ".hidden",
+            "100%_glitchless",
+            "Pok\u{e9}mon \u{2605}",
+            "tab\there",
+        ] {
+            let escaped = escape_file_name(name);
+            assert!(!escaped.contains(['/', '\\', '.', '_']), "{escaped}");
+            assert!(!escaped.chars().any(char::is_control), "{escaped}");
+            assert_eq!(unescape_file_name(&escaped), name);
+        }
+    }
+
+    #[test]
+    fn speedrun_folder_stays_in_the_data_folder() {
+        assert_eq!(escape_file_name(".."), "%2E%2E");
+        let folder = speedrun_folder_name("..", "../..");
+        assert_eq!(folder, "%2E%2E/%2E%2E%2F%2E%2E");
+        assert!(Path::new(&folder)
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_))));
+        assert_ne!(
+            speedrun_folder_name("a_b", "c"),
+            speedrun_folder_name("a", "b_c")
+        );
+    }
+
+    /// Write flat legacy files of speedrun `settings` in its data folder,
+    /// named with `legacy_stem`
+    fn write_legacy_files(settings: &SpeedrunSettings, legacy_stem: &str) {
+        let data_folder = &settings.data_folder;
+        fs::create_dir_all(data_folder.join("backups")).unwrap();
+        fs::write(
+            data_folder.join(format!("{legacy_stem}.txt")),
+            toml::to_string(settings).unwrap(),
+        )
+        .unwrap();
+        fs::write(data_folder.join(format!("{legacy_stem}.lss")), "run").unwrap();
+        fs::write(
+            data_folder.join(format!("{legacy_stem}_practice.log")),
+            "log",
+        )
+        .unwrap();
+        fs::write(
+            data_folder
+                .join("backups")
+                .join(format!("{legacy_stem}_20240101-000000-000.lss")),
+            "backup",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn flat_speedrun_files_are_moved_to_their_folder() {
+        let settings = speedrun_settings("migrate-flat");
+        write_legacy_files(&settings, "Game_Any%");
+        let mut configuration = configuration(&settings);
+        configuration.default_speedrun_name = Some("Game_Any%.txt".to_string());
+
+        migrate_speedrun_files(&mut configuration).unwrap_or_else(|e| panic!("{e}"));
+
+        assert!(settings.get_file_path().is_file());
+        assert_eq!(
+            fs::read_to_string(settings.get_run_file_path()).unwrap(),
+            "run"
+        );
+        assert!(practice_log_path(&settings).is_file());
+        let backups = list_run_backups(&settings).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].0, "20240101-000000-000");
+        assert!(!settings.data_folder.join("backups").exists());
+        assert!(!settings.data_folder.join("Game_Any%.lss").exists());
+        assert_eq!(
+            configuration.default_speedrun_name.as_deref(),
+            Some("Game/Any%25")
+        );
+        assert!(configuration.path.is_file());
+        assert!(find_speedrun(&configuration, "Game", "Any%").is_ok());
+    }
+
+    #[test]
+    fn files_with_escaped_names_are_moved_without_overwriting() {
+        let settings = speedrun_settings("migrate-escaped");
+        write_legacy_files(&settings, "Game_Any%25");
+        fs::create_dir_all(settings.get_folder()).unwrap();
+        fs::write(settings.get_run_file_path(), "newer run").unwrap();
+        let mut configuration = configuration(&settings);
+
+        migrate_speedrun_files(&mut configuration).unwrap_or_else(|e| panic!("{e}"));
+
+        assert!(settings.get_file_path().is_file());
+        assert_eq!(
+            fs::read_to_string(settings.get_run_file_path()).unwrap(),
+            "newer run"
+        );
+        assert!(settings.data_folder.join("Game_Any%25.lss").exists());
+        assert!(practice_log_path(&settings).is_file());
+        assert!(configuration.default_speedrun_name.is_none());
+    }
+
+    #[test]
+    fn split_icons_are_stored_resized_and_loaded() {
+        let mut settings = speedrun_settings("store-icons");
+        let icon = DynamicImage::new_rgba8(16, 16);
+        assert!(store_split_icon(&mut settings, 3, &icon).is_err());
+
+        store_split_icon(&mut settings, 1, &icon).unwrap();
+
+        assert_eq!(settings.split_icons, ["", "icons/2.png", ""]);
+        let stored = image::open(settings.get_folder().join("icons/2.png")).unwrap();
+        assert_eq!(
+            (stored.width(), stored.height()),
+            (SPLIT_ICON_SIZE, SPLIT_ICON_SIZE)
+        );
+        let mut run = run_named(&settings, "icons");
+        load_split_icons(&settings, &mut run);
+        assert!(run.segment(0).icon().is_empty());
+        assert!(!run.segment(1).icon().is_empty());
+
+        remove_split_icon(&mut settings, 1).unwrap();
+        assert_eq!(settings.split_icons[1], "");
+        assert!(!settings.get_folder().join("icons/2.png").exists());
+    }
+
+    #[test]
+    fn unreadable_split_icons_are_skipped() {
+        let mut settings = speedrun_settings("unreadable-icons");
+        // a folder cannot be read as an image
+        fs::create_dir_all(settings.get_folder().join("icons/3.png")).unwrap();
+        fs::write(settings.data_folder.join("outside.png"), "").unwrap();
+        settings.split_icons = vec![
+            "icons/1.png".to_string(),
+            "../../outside.png".to_string(),
+            "icons/3.png".to_string(),
+        ];
+        assert!(settings.get_split_icon_path("../../outside.png").is_none());
+        assert!(settings.get_split_icon_path("/tmp/icon.png").is_none());
+
+        let mut run = run_named(&settings, "icons");
+        load_split_icons(&settings, &mut run);
+
+        assert!(run.segments().iter().all(|s| s.icon().is_empty()));
+    }
+
+    /// Returns keybinding of imported speedruns, so no key is asked
+    fn imported_keybinding() -> UserKeybinding<'static> {
+        UserKeybinding::new(
+            Some("Numpad1"),
+            Some("Numpad3"),
+            Some("Numpad5"),
+            Some("Numpad7"),
+            Some("Numpad9"),
+            Some("Numpad8"),
+            Some("Numpad2"),
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn livesplit_splits_are_imported_with_icons() {
+        let settings = speedrun_settings("import");
+        let configuration = configuration(&settings);
+        let mut run = run_named(&settings, "Any%");
+        run.set_game_name("Imported game");
+        let mut icon = Cursor::new(vec![]);
+        DynamicImage::new_rgba8(8, 8)
+            .write_to(&mut icon, ImageOutputFormat::Png)
+            .unwrap();
+        run.segment_mut(2).set_icon(Image::new(icon.get_ref()));
+        let mut run_content = String::new();
+        livesplit::save_run(&run, &mut run_content).unwrap();
+        let path = settings.data_folder.join("splits.lss");
+        fs::write(&path, run_content).unwrap();
+
+        let (imported, imported_run) = import_speedrun(
+            &configuration,
+            &path,
+            None,
+            Some("Glitchless"),
+            Some("game"),
+            &imported_keybinding(),
+        )
+        .unwrap();
+
+        assert_eq!(imported.get_game_name(), "Imported game");
+        assert_eq!(imported.get_category_name(), "Glitchless");
+        assert_eq!(imported_run.category_name(), "Glitchless");
+        assert_eq!(imported.get_split_names(), settings.get_split_names());
+        assert!(matches!(
+            imported.get_timing_method(),
+            TimingMethod::GameTime
+        ));
+        assert_eq!(imported.split_icons, ["", "", "icons/3.png"]);
+        assert!(imported.get_folder().join("icons/3.png").is_file());
+
+        save_speedrun_settings_to_file(&imported).unwrap();
+        assert!(import_speedrun(
+            &configuration,
+            &path,
+            None,
+            Some("Glitchless"),
+            None,
+            &imported_keybinding(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn splits_without_names_or_splits_are_rejected() {
+        let settings = speedrun_settings("import-invalid");
+        let configuration = configuration(&settings);
+        let path = settings.data_folder.join("splits.lss");
+        let mut run_content = String::new();
+        livesplit::save_run(&run_named(&settings, ""), &mut run_content).unwrap();
+        fs::write(&path, run_content).unwrap();
+        assert!(import_speedrun(
+            &configuration,
+            &path,
+            None,
+            None,
+            None,
+            &imported_keybinding()
+        )
+        .is_err());
+
+        fs::write(&path, "not splits").unwrap();
+        assert!(import_speedrun(
+            &configuration,
+            &path,
+            Some("Game"),
+            Some("Any%"),
+            None,
+            &imported_keybinding(),
+        )
+        .is_err());
+    }
+}
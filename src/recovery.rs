@@ -0,0 +1,573 @@
+//! Recover an attempt in progress after pmls crashed or was killed
+//!
+//! While an attempt is in progress, a journal of the timer (split times,
+//! current time, pause state) is written to the data folder every second. It
+//! is removed once the attempt is reset. When the speedrun is launched again,
+//! the attempt of a remaining journal is either resumed or added to the
+//! attempt history as if it was reset. A journal that cannot be parsed is
+//! kept aside for inspection
+//!
+//! NOTE: the timer does not allow setting split times, so a resumed attempt
+//! continues from the journaled time but its completed splits are skipped.
+//! It only counts as a reset attempt: best segments, segment history and
+//! personal best are never updated with it. Resetting the attempt instead
+//! keeps every split time
+use crate::persistence::write_atomically;
+use crate::time_format::TimeFormat;
+use crate::timer_controls::TimerControlError;
+use crate::ui::Splits;
+use livesplit_core::TimerPhase::{Ended, NotRunning, Paused};
+use livesplit_core::{AtomicDateTime, Run, Time, TimeSpan, Timer};
+use livesplit_core::{TimerPhase, TimingMethod};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// Delay between two writes of the journal
+const JOURNAL_DELAY: Duration = Duration::from_secs(1);
+
+/// Errors while recovering an attempt
+#[derive(Debug)]
+pub enum RecoveryError {
+    /// Error with filesystem
+    IO(std::io::Error),
+    /// Journal is not valid JSON
+    Parse(serde_json::Error),
+    /// Unknown choice to recover the attempt
+    UnknownChoice(String),
+}
+
+impl fmt::Display for RecoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecoveryError::IO(e) => writeln!(f, "{e}"),
+            RecoveryError::Parse(e) => writeln!(f, "Attempt journal could not be parsed: {e}"),
+            RecoveryError::UnknownChoice(choice) => writeln!(
+                f,
+                "Unknown choice \"{choice}\" to recover attempt (expected \"resume\" or \"reset\")"
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for RecoveryError {
+    fn from(e: std::io::Error) -> Self {
+        RecoveryError::IO(e)
+    }
+}
+
+impl From<serde_json::Error> for RecoveryError {
+    fn from(e: serde_json::Error) -> Self {
+        RecoveryError::Parse(e)
+    }
+}
+
+/// What to do with the attempt of a journal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Recovery {
+    /// Continue the attempt
+    Resume,
+    /// Add the attempt to the attempt history
+    Reset,
+}
+
+impl Recovery {
+    /// Returns choice named `name` ("resume" or "reset")
+    ///
+    /// # Errors
+    /// Returns an error if `name` is unknown
+    pub fn from_name(name: &str) -> Result<Recovery, RecoveryError> {
+        match name {
+            "resume" => Ok(Recovery::Resume),
+            "reset" => Ok(Recovery::Reset),
+            _ => Err(RecoveryError::UnknownChoice(name.to_string())),
+        }
+    }
+}
+
+/// Phase of the journaled attempt
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum JournalPhase {
+    Running,
+    Paused,
+    Ended,
+}
+
+/// Real and game time, in seconds
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+struct JournalTime {
+    real_time: Option<f64>,
+    game_time: Option<f64>,
+}
+
+impl From<Time> for JournalTime {
+    fn from(time: Time) -> Self {
+        JournalTime {
+            real_time: time.real_time.map(|t| t.total_seconds()),
+            game_time: time.game_time.map(|t| t.total_seconds()),
+        }
+    }
+}
+
+impl From<JournalTime> for Time {
+    fn from(time: JournalTime) -> Self {
+        Time::new()
+            .with_real_time(time.real_time.map(TimeSpan::from_seconds))
+            .with_game_time(time.game_time.map(TimeSpan::from_seconds))
+    }
+}
+
+/// State of an attempt in progress
+#[derive(Serialize, Deserialize)]
+pub struct Journal {
+    /// attempt count of the run, including this attempt
+    attempt_count: u32,
+    /// start of the attempt (unix time in milliseconds)
+    attempt_started: i64,
+    /// last write of the journal (unix time in milliseconds)
+    written_at: i64,
+    phase: JournalPhase,
+    /// split times of splits before the current split (skipped splits have no
+    /// time)
+    split_times: Vec<JournalTime>,
+    current_time: JournalTime,
+    game_time_paused: bool,
+    /// total time the attempt was paused for (seconds)
+    pause_time: Option<f64>,
+    /// attempt was already resumed once, so its first split times are missing
+    #[serde(default)]
+    resumed: bool,
+}
+
+impl Journal {
+    /// Returns state of attempt in progress of `timer`, if any. Start of the
+    /// attempt is kept from `previous` journal of the same attempt. A
+    /// `resumed` attempt is recorded without its split times when reset
+    fn new(timer: &Timer, previous: Option<&Journal>, resumed: bool) -> Option<Journal> {
+        let phase = match timer.current_phase() {
+            NotRunning => return None,
+            TimerPhase::Running => JournalPhase::Running,
+            Paused => JournalPhase::Paused,
+            Ended => JournalPhase::Ended,
+        };
+        let completed = timer.current_split_index().unwrap_or(0);
+        let run = timer.run();
+        let now = AtomicDateTime::now();
+        let attempt_started = match previous {
+            Some(previous) if previous.attempt_count == run.attempt_count() => {
+                previous.attempt_started
+            }
+            _ => unix_milliseconds(now) - duration_milliseconds(timer.current_attempt_duration()),
+        };
+        Some(Journal {
+            attempt_count: run.attempt_count(),
+            attempt_started,
+            written_at: unix_milliseconds(now),
+            phase,
+            split_times: run.segments()[..completed.min(run.len())]
+                .iter()
+                .map(|s| s.split_time().into())
+                .collect(),
+            current_time: timer.snapshot().current_time().into(),
+            game_time_paused: timer.is_game_time_paused(),
+            pause_time: timer.get_pause_time().map(|t| t.total_seconds()),
+            resumed,
+        })
+    }
+
+    /// Returns a short description of the attempt of `run` for the user, with
+    /// times formatted with `time_format`
+    #[must_use]
+    pub fn describe(&self, run: &Run, time_format: TimeFormat) -> String {
+        let time = self
+            .current_time
+            .real_time
+            .map_or(TimeSpan::zero(), TimeSpan::from_seconds);
+        let split = match self.split_times.len() {
+            i if i < run.len() => format!("at split \"{}\"", run.segment(i).name()),
+            _ => "after the last split".to_string(),
+        };
+        format!(
+            "Attempt {} was interrupted {split} ({})",
+            self.attempt_count,
+            time_format.format_time(time)
+        )
+    }
+
+    /// Returns true if the journaled attempt was finished but not reset yet.
+    /// Such attempt cannot be resumed
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.phase == JournalPhase::Ended
+    }
+
+    /// Continue the journaled attempt with `timer`, which must not be running.
+    /// Returns offset of the run, replaced so the attempt continues at the
+    /// journaled time. It must be restored once the attempt is reset, and the
+    /// attempt must then be added to the attempt history as a reset attempt
+    /// without updating best segments, segment history or personal best
+    #[must_use]
+    pub fn resume(&self, timer: &mut Timer) -> TimeSpan {
+        let mut run = timer.run().clone();
+        let offset = run.offset();
+        run.set_offset(
+            self.current_time
+                .real_time
+                .map_or(offset, TimeSpan::from_seconds),
+        );
+        // NOTE: starting the attempt counts it again
+        run.set_attempt_count(self.attempt_count.saturating_sub(1));
+        if timer.set_run(run).is_err() {
+            error!("Attempt could not be resumed");
+            return offset;
+        }
+        timer.start();
+        for _ in &self.split_times {
+            timer.skip_split();
+        }
+        if let Some(game_time) = self.current_time.game_time {
+            timer.initialize_game_time();
+            timer.set_game_time(TimeSpan::from_seconds(game_time));
+            if self.game_time_paused {
+                timer.pause_game_time();
+            }
+        }
+        if self.phase == JournalPhase::Paused {
+            timer.pause();
+        }
+        info!("Resumed attempt {}", self.attempt_count);
+        offset
+    }
+
+    /// Add the journaled attempt to the attempt history of `run`, updating
+    /// best segments, segment history and personal best (compared with
+    /// `timing_method`) like a reset of the timer. An attempt that was
+    /// resumed before is only added to the attempt history, without time
+    pub fn reset(&self, run: &mut Run, timing_method: TimingMethod) {
+        if self.resumed {
+            run.set_attempt_count(run.attempt_count().max(self.attempt_count));
+            let now = AtomicDateTime::now();
+            run.add_attempt(
+                Time::default(),
+                None,
+                Some(date_time(now, self.written_at)),
+                self.pause_time.map(TimeSpan::from_seconds),
+            );
+            info!(
+                "Resumed attempt {} added to attempt history as a reset",
+                self.attempt_count
+            );
+            return;
+        }
+        let completed = self.split_times.len().min(run.len());
+        for (segment, time) in run.segments_mut().iter_mut().zip(&self.split_times) {
+            segment.set_split_time((*time).into());
+        }
+        run.set_attempt_count(run.attempt_count().max(self.attempt_count));
+        let time = if self.is_finished() {
+            self.current_time.into()
+        } else {
+            Time::default()
+        };
+        let now = AtomicDateTime::now();
+        run.add_attempt(
+            time,
+            Some(date_time(now, self.attempt_started)),
+            Some(date_time(now, self.written_at)),
+            self.pause_time.map(TimeSpan::from_seconds),
+        );
+        update_best_segments(run);
+        if self.is_finished() {
+            update_personal_best(run, timing_method);
+        }
+        run.update_segment_history(completed);
+        for segment in run.segments_mut() {
+            segment.clear_split_time();
+        }
+        run.fix_splits();
+        run.regenerate_comparisons();
+        info!("Attempt {} added to attempt history", self.attempt_count);
+    }
+}
+
+/// Write journal of attempt in progress of `timer` to `path` at a fixed
+/// cadence. Journal is removed when no attempt is in progress. Nothing is
+/// written in practice mode
+///
+/// # Panics
+/// Panics if timer or splits lock cannot be acquired
+pub fn start_journal(timer: Arc<RwLock<Timer>>, splits: Arc<RwLock<Splits>>, path: PathBuf) {
+    thread::spawn(move || {
+        let mut previous: Option<Journal> = None;
+        loop {
+            thread::sleep(JOURNAL_DELAY);
+            let timer = match timer.read().map_err(TimerControlError::TimerReadLock) {
+                Ok(timer) => timer,
+                Err(e) => {
+                    error!("{e}");
+                    panic!("{e}") // cannot recover
+                }
+            };
+            let (practice, resumed) = match splits.read().map_err(TimerControlError::SplitsReadLock)
+            {
+                Ok(splits) => (splits.is_practicing(), splits.is_resumed_attempt()),
+                Err(e) => {
+                    error!("{e}");
+                    panic!("{e}") // cannot recover
+                }
+            };
+            let journal = if practice {
+                None
+            } else {
+                Journal::new(&timer, previous.as_ref(), resumed)
+            };
+            drop(timer);
+            let result = match &journal {
+                Some(journal) => match serde_json::to_vec(journal) {
+                    Ok(content) => write_atomically(&path, &content),
+                    Err(e) => {
+                        error!("{e}");
+                        continue;
+                    }
+                },
+                // NOTE: removed file is not checked again
+                None if previous.is_some() => remove_journal(&path),
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                error!("\"{}\": {e}", path.display());
+            }
+            previous = journal;
+        }
+    });
+}
+
+/// Returns journal of interrupted attempt at `path`, if any
+///
+/// # Errors
+/// Returns an error if the journal cannot be read or parsed
+pub fn read_journal(path: &Path) -> Result<Option<Journal>, RecoveryError> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(serde_json::from_slice(&content)?)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Remove journal at `path`. Missing journal is ignored
+///
+/// # Errors
+/// Returns an error if the journal cannot be removed
+pub fn remove_journal(path: &Path) -> Result<(), std::io::Error> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Keep journal at `path` that cannot be parsed aside, so it is not read
+/// again. Returns its new path
+///
+/// # Errors
+/// Returns an error if the journal cannot be renamed
+pub fn set_journal_aside(path: &Path) -> Result<PathBuf, std::io::Error> {
+    let mut aside_path = path.as_os_str().to_owned();
+    aside_path.push(".corrupt");
+    let aside_path = PathBuf::from(aside_path);
+    fs::rename(path, &aside_path)?;
+    Ok(aside_path)
+}
+
+/// Returns `date` as unix time in milliseconds
+fn unix_milliseconds(date: AtomicDateTime) -> i64 {
+    i64::try_from(date.time.unix_timestamp_nanos() / 1_000_000).unwrap_or_default()
+}
+
+/// Returns `duration` in milliseconds
+fn duration_milliseconds(duration: TimeSpan) -> i64 {
+    i64::try_from(duration.to_duration().whole_milliseconds()).unwrap_or_default()
+}
+
+/// Returns date of unix time `milliseconds`, computed from current date `now`
+fn date_time(now: AtomicDateTime, milliseconds: i64) -> AtomicDateTime {
+    #[allow(clippy::cast_precision_loss)]
+    let elapsed = TimeSpan::from_milliseconds((unix_milliseconds(now) - milliseconds) as f64);
+    AtomicDateTime::new(
+        now.time - elapsed.to_duration(),
+        now.synced_with_atomic_clock,
+    )
+}
+
+/// Keep split times of `run` that beat their best segment time, like the
+/// timer does on reset
+fn update_best_segments(run: &mut Run) {
+    for timing_method in TimingMethod::all() {
+        let mut previous_split_time = TimeSpan::zero();
+        for segment in run.segments_mut() {
+            let Some(split_time) = segment.split_time()[timing_method] else {
+                continue;
+            };
+            let segment_time = split_time - previous_split_time;
+            previous_split_time = split_time;
+            let best_segment_time = segment.best_segment_time()[timing_method];
+            if best_segment_time.is_none_or(|best| segment_time < best) {
+                segment.best_segment_time_mut()[timing_method] = Some(segment_time);
+            }
+        }
+    }
+}
+
+/// Make split times of finished `run` the personal best if its final time
+/// with `timing_method` beats it, like the timer does on reset
+fn update_personal_best(run: &mut Run, timing_method: TimingMethod) {
+    let Some(last_segment) = run.segments().last() else {
+        return;
+    };
+    let final_time = last_segment.split_time()[timing_method];
+    let personal_best = last_segment.personal_best_split_time()[timing_method];
+    if final_time.is_none_or(|t| personal_best.is_some_and(|pb| t >= pb)) {
+        return;
+    }
+    run.import_pb_into_segment_history();
+    run.fix_splits();
+    for segment in run.segments_mut() {
+        let split_time = segment.split_time();
+        segment.set_personal_best_split_time(split_time);
+    }
+    run.clear_run_id();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{split_at, timer};
+
+    /// Returns timer using game time of a run with segments "Intro" and "End"
+    /// and a personal best split at 10s and 20s
+    fn timer_with_personal_best() -> Timer {
+        let mut timer = timer(&["Intro", "End"], TimingMethod::GameTime);
+        split_at(&mut timer, &[10.0, 20.0]);
+        timer.reset(true);
+        timer
+    }
+
+    /// Returns best segment and personal best split times (game time) of
+    /// `run`
+    fn game_times(run: &Run) -> Vec<(Option<TimeSpan>, Option<TimeSpan>)> {
+        run.segments()
+            .iter()
+            .map(|s| {
+                (
+                    s.best_segment_time().game_time,
+                    s.personal_best_split_time().game_time,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns run of `timer` with its attempt in progress recovered from a
+    /// journal, and run of `timer` after resetting the attempt
+    fn recovered_and_reset_runs(timer: &mut Timer, split_times: &[f64]) -> (Run, Run) {
+        let mut recovered = timer.run().clone();
+        split_at(timer, split_times);
+        let journal = Journal::new(timer, None, false).unwrap();
+        journal.reset(&mut recovered, TimingMethod::GameTime);
+        timer.reset(true);
+        (recovered, timer.run().clone())
+    }
+
+    #[test]
+    fn unfinished_attempt_keeps_best_segments() {
+        let mut timer = timer_with_personal_best();
+
+        let (recovered, reset) = recovered_and_reset_runs(&mut timer, &[5.0]);
+
+        assert_eq!(game_times(&recovered), game_times(&reset));
+        assert_eq!(
+            game_times(&recovered)[0],
+            (
+                Some(TimeSpan::from_seconds(5.0)),
+                Some(TimeSpan::from_seconds(10.0))
+            )
+        );
+        assert_eq!(recovered.attempt_history().len(), 2);
+        assert_eq!(recovered.attempt_count(), reset.attempt_count());
+        assert!(recovered.attempt_history()[1].time().game_time.is_none());
+    }
+
+    #[test]
+    fn finished_attempt_becomes_personal_best() {
+        let mut timer = timer_with_personal_best();
+
+        let (recovered, reset) = recovered_and_reset_runs(&mut timer, &[5.0, 15.0]);
+
+        assert_eq!(game_times(&recovered), game_times(&reset));
+        assert_eq!(
+            game_times(&recovered)[1].1,
+            Some(TimeSpan::from_seconds(15.0))
+        );
+        assert_eq!(
+            recovered.attempt_history()[1].time().game_time,
+            Some(TimeSpan::from_seconds(15.0))
+        );
+    }
+
+    #[test]
+    fn slower_finished_attempt_keeps_personal_best() {
+        let mut timer = timer_with_personal_best();
+
+        let (recovered, reset) = recovered_and_reset_runs(&mut timer, &[12.0, 25.0]);
+
+        assert_eq!(game_times(&recovered), game_times(&reset));
+        assert_eq!(
+            game_times(&recovered)[1].1,
+            Some(TimeSpan::from_seconds(20.0))
+        );
+    }
+
+    #[test]
+    fn resumed_attempt_is_only_counted() {
+        let mut timer = timer_with_personal_best();
+        let mut recovered = timer.run().clone();
+        let times_before = game_times(&recovered);
+        split_at(&mut timer, &[5.0, 15.0]);
+
+        let journal = Journal::new(&timer, None, true).unwrap();
+        journal.reset(&mut recovered, TimingMethod::GameTime);
+
+        assert_eq!(game_times(&recovered), times_before);
+        assert_eq!(recovered.attempt_history().len(), 2);
+        assert!(recovered.attempt_history()[1].time().game_time.is_none());
+        assert_eq!(recovered.attempt_count(), 2);
+    }
+
+    #[test]
+    fn journal_is_read_back_or_set_aside() {
+        let folder = std::env::temp_dir().join(format!("pmls-test-{}-journal", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("journal.json");
+        let mut timer = timer_with_personal_best();
+        split_at(&mut timer, &[5.0]);
+        let journal = Journal::new(&timer, None, false).unwrap();
+        write_atomically(&path, &serde_json::to_vec(&journal).unwrap()).unwrap();
+
+        let read = read_journal(&path).unwrap().unwrap();
+        assert_eq!(read.split_times.len(), 1);
+        assert_eq!(read.attempt_count, 2);
+        assert!(!read.resumed);
+
+        fs::write(&path, "{").unwrap();
+        assert!(matches!(read_journal(&path), Err(RecoveryError::Parse(_))));
+        let aside_path = set_journal_aside(&path).unwrap();
+        assert!(aside_path.exists());
+        assert!(read_journal(&path).unwrap().is_none());
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+//! Practice a range of segments: the timer starts at the first practiced split
+//! and each practiced segment is compared against its own best and personal
+//! best segment times
+//!
+//! Segment practice always runs in practice mode. Segment times are appended to
+//! a practice log file instead of the segment history of the run
+use livesplit_core::analysis;
+use livesplit_core::{AtomicDateTime, Timer};
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Errors while selecting practiced segments
+#[derive(Debug)]
+pub enum SegmentPracticeError {
+    /// No split with this name or number
+    UnknownSplit(String),
+    /// Last practiced split comes before the first one
+    InvalidRange(String, String),
+}
+
+impl fmt::Display for SegmentPracticeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SegmentPracticeError::UnknownSplit(split) => {
+                write!(f, "No split named or numbered \"{split}\"")
+            }
+            SegmentPracticeError::InvalidRange(from, to) => {
+                write!(f, "Split \"{to}\" comes before split \"{from}\"")
+            }
+        }
+    }
+}
+
+/// Range of practiced segments
+#[derive(Clone, Debug)]
+pub struct SegmentPractice {
+    start: usize,
+    end: usize,
+    log_path: PathBuf,
+}
+
+impl SegmentPractice {
+    /// Practice segments from split `from` to split `to` (only `from` if
+    /// absent) of `split_names`. Splits are given by name or by number
+    /// (starting at 1). Segment times are appended to `log_path`
+    ///
+    /// # Errors
+    /// Returns an error if a split cannot be found or if the range is empty
+    pub fn new(
+        split_names: &[String],
+        from: &str,
+        to: Option<&str>,
+        log_path: PathBuf,
+    ) -> Result<SegmentPractice, SegmentPracticeError> {
+        let start = find_split(split_names, from)?;
+        let end = match to {
+            Some(to) => find_split(split_names, to)?,
+            None => start,
+        };
+        if end < start {
+            return Err(SegmentPracticeError::InvalidRange(
+                from.to_string(),
+                to.unwrap_or_default().to_string(),
+            ));
+        }
+        Ok(SegmentPractice {
+            start,
+            end,
+            log_path,
+        })
+    }
+
+    /// Returns index of first practiced split
+    #[must_use]
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns index of last practiced split
+    #[must_use]
+    pub fn get_end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns true if split `i` is practiced
+    #[must_use]
+    pub fn contains(&self, i: usize) -> bool {
+        (self.start..=self.end).contains(&i)
+    }
+
+    /// Append segment time of split `i` of `timer` to the practice log. Each
+    /// line holds the date, split number, split name, timing method and
+    /// segment time in seconds, separated by tabs
+    ///
+    /// # Errors
+    /// Returns an error if the practice log cannot be written
+    pub fn record(&self, timer: &Timer, i: usize) -> Result<(), std::io::Error> {
+        let timing_method = timer.current_timing_method();
+        let Some(segment_time) = analysis::previous_segment_time(timer, i, timing_method) else {
+            return Ok(());
+        };
+        let mut log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_path)?;
+        writeln!(
+            log,
+            "{}\t{}\t{}\t{timing_method:?}\t{:.3}",
+            AtomicDateTime::now().time,
+            i + 1,
+            timer.run().segment(i).name(),
+            segment_time.total_seconds()
+        )
+    }
+}
+
+/// Returns index of split named `split`, or numbered `split` (starting at 1)
+///
+/// # Errors
+/// Returns an error if no split matches
+pub fn find_split(split_names: &[String], split: &str) -> Result<usize, SegmentPracticeError> {
+    if let Some(i) = split_names.iter().position(|name| name == split) {
+        return Ok(i);
+    }
+    match split.parse::<usize>() {
+        Ok(number) if (1..=split_names.len()).contains(&number) => Ok(number - 1),
+        _ => Err(SegmentPracticeError::UnknownSplit(split.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_names() -> Vec<String> {
+        ["Intro", "2", "Boss", "End"]
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn splits_are_found_by_name_then_number() {
+        let names = split_names();
+        assert_eq!(find_split(&names, "Boss").unwrap(), 2);
+        assert_eq!(find_split(&names, "1").unwrap(), 0);
+        // split named "2" comes before split number 2
+        assert_eq!(find_split(&names, "2").unwrap(), 1);
+        assert_eq!(find_split(&names, "4").unwrap(), 3);
+        assert!(find_split(&names, "0").is_err());
+        assert!(find_split(&names, "5").is_err());
+        assert!(find_split(&names, "boss").is_err());
+    }
+
+    #[test]
+    fn practiced_range_is_inclusive() {
+        let names = split_names();
+        let practice = SegmentPractice::new(&names, "2", Some("Boss"), PathBuf::new()).unwrap();
+        assert!(!practice.contains(0));
+        assert!(practice.contains(1));
+        assert!(practice.contains(2));
+        assert!(!practice.contains(3));
+
+        let practice = SegmentPractice::new(&names, "Boss", None, PathBuf::new()).unwrap();
+        assert_eq!((practice.get_start(), practice.get_end()), (2, 2));
+    }
+
+    #[test]
+    fn reversed_range_is_rejected() {
+        assert!(matches!(
+            SegmentPractice::new(&split_names(), "End", Some("Intro"), PathBuf::new()),
+            Err(SegmentPracticeError::InvalidRange(_, _))
+        ));
+    }
+}
@@ -0,0 +1,307 @@
+//! livesplit server compatible TCP listener to control the timer with external
+//! tools
+//!
+//! Commands are sent one per line. Commands starting with `get` are answered
+//! with one line. Supported commands:
+//!
+//! * `starttimer`, `startorsplit`, `split`, `unsplit`, `skipsplit`
+//! * `pause`, `resume`, `reset`
+//! * `initgametime`, `setgametime TIME`, `setloadingtimes TIME`,
+//!   `pausegametime`, `unpausegametime`
+//! * `setcomparison COMPARISON`, `switchto realtime|gametime`
+//! * `getdelta [COMPARISON]`, `getlastsplittime`, `getcomparisonsplittime`,
+//!   `getcurrenttime`, `getfinaltime [COMPARISON]`, `getsplitindex`,
+//!   `getcurrentsplitname`, `getprevioussplitname`, `getcurrenttimerphase`
+use crate::time_format::{format_delta, format_time, TimeFormat};
+use crate::timer_controls::{
+    initialize_game_time, pause, pause_game_time, reset_without_confirmation, resume_game_time,
+    set_comparison, set_game_time, set_loading_times, set_timing_method, skip_split,
+    start_or_split_timer, undo_split, unpause, TimerControlError,
+};
+use crate::ui::Splits;
+use livesplit_core::analysis;
+use livesplit_core::TimerPhase::{Ended, NotRunning, Paused, Running};
+use livesplit_core::{TimeSpan, Timer, TimerPhase, TimingMethod};
+use log::{debug, error, info, warn};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+/// Port used by livesplit server
+pub const DEFAULT_PORT: u16 = 16834;
+
+/// Listen on localhost `port` for livesplit server commands controlling
+/// `timer` and keep `splits` display in sync. Times are answered with
+/// `time_format`. Each client is served in its own thread
+///
+/// # Errors
+/// Returns an error if `port` cannot be listened on
+pub fn start_livesplit_server(
+    port: u16,
+    timer: Arc<RwLock<Timer>>,
+    splits: Arc<RwLock<Splits>>,
+    time_format: TimeFormat,
+) -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    info!("LiveSplit Server listening on port {port}");
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let timer = timer.clone();
+                    let splits = splits.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = handle_client(stream, &timer, &splits, time_format) {
+                            error!("{e}");
+                        }
+                    });
+                }
+                Err(e) => error!("{e}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Execute commands sent by client `stream` until it disconnects
+fn handle_client(
+    stream: TcpStream,
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    time_format: TimeFormat,
+) -> Result<(), std::io::Error> {
+    let peer = stream.peer_addr()?;
+    info!("LiveSplit Server client connected: {peer}");
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        let command = line.trim();
+        if command.is_empty() {
+            continue;
+        }
+        debug!("LiveSplit Server command: {command}");
+        if let Some(response) = execute_command(command, timer, splits, time_format) {
+            writer.write_all(format!("{response}\r\n").as_bytes())?;
+        }
+    }
+    info!("LiveSplit Server client disconnected: {peer}");
+    Ok(())
+}
+
+/// Execute `command` on `timer` and returns response, formatted with
+/// `time_format`, if command expects one
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub(crate) fn execute_command(
+    command: &str,
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    time_format: TimeFormat,
+) -> Option<String> {
+    let (name, argument) = match command.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, Some(argument.trim())),
+        None => (command, None),
+    };
+    if name.starts_with("get") {
+        let timer = match timer.read().map_err(TimerControlError::TimerReadLock) {
+            Ok(timer) => timer,
+            Err(e) => {
+                error!("{e}");
+                panic!("{e}") // cannot recover
+            }
+        };
+        return query(name, argument, &timer, time_format);
+    }
+
+    let phase = match timer.read().map_err(TimerControlError::TimerReadLock) {
+        Ok(timer) => timer.current_phase(),
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    match (name, argument) {
+        ("starttimer", _) if phase == NotRunning => start_or_split_timer(timer, splits),
+        ("split", _) if phase == Running => start_or_split_timer(timer, splits),
+        ("startorsplit", _) => start_or_split_timer(timer, splits),
+        ("unsplit", _) => undo_split(timer, splits),
+        ("skipsplit", _) => skip_split(timer, splits),
+        ("pause", _) => pause(timer),
+        ("resume", _) => unpause(timer),
+        ("reset", _) => reset_without_confirmation(timer, splits),
+        ("initgametime", _) => initialize_game_time(timer),
+        ("setgametime", Some(time)) => match time.parse::<TimeSpan>() {
+            Ok(time) => set_game_time(timer, time),
+            Err(e) => warn!("Invalid game time \"{time}\": {e}"),
+        },
+        ("setloadingtimes", Some(time)) => match time.parse::<TimeSpan>() {
+            Ok(time) => set_loading_times(timer, time),
+            Err(e) => warn!("Invalid loading times \"{time}\": {e}"),
+        },
+        ("pausegametime", _) => pause_game_time(timer),
+        ("unpausegametime", _) => resume_game_time(timer),
+        ("setcomparison", Some(comparison)) => set_comparison(timer, splits, comparison),
+        ("switchto", Some("realtime")) => set_timing_method(timer, splits, TimingMethod::RealTime),
+        ("switchto", Some("gametime")) => set_timing_method(timer, splits, TimingMethod::GameTime),
+        ("starttimer" | "split", _) => {}
+        _ => warn!("Unsupported LiveSplit Server command \"{command}\""),
+    }
+    None
+}
+
+/// Answer `get` query `name` with optional `argument` from `timer` state
+fn query(
+    name: &str,
+    argument: Option<&str>,
+    timer: &Timer,
+    time_format: TimeFormat,
+) -> Option<String> {
+    let snapshot = timer.snapshot();
+    let run = timer.run();
+    let timing_method = timer.current_timing_method();
+    let comparison = argument.unwrap_or_else(|| timer.current_comparison());
+    let split_index = timer.current_split_index();
+    let previous_segment = split_index
+        .and_then(|i| i.checked_sub(1))
+        .and_then(|i| run.segments().get(i));
+    let response = match name {
+        "getdelta" => {
+            let (delta, _) = analysis::delta::calculate(&snapshot, comparison);
+            format_delta(delta, time_format)
+        }
+        "getlastsplittime" => format_time(
+            previous_segment.and_then(|s| s.split_time()[timing_method]),
+            time_format,
+        ),
+        "getcomparisonsplittime" => format_time(
+            timer
+                .current_split()
+                .and_then(|s| s.comparison(comparison)[timing_method]),
+            time_format,
+        ),
+        "getcurrenttime" => format_time(snapshot.current_time()[timing_method], time_format),
+        "getfinaltime" => {
+            let last_segment = run.segments().last()?;
+            let final_time = if timer.current_phase() == Ended {
+                last_segment.split_time()[timing_method]
+            } else {
+                last_segment.comparison(comparison)[timing_method]
+            };
+            format_time(final_time, time_format)
+        }
+        "getsplitindex" => split_index.map_or("-1".to_string(), |i| i.to_string()),
+        "getcurrentsplitname" => timer
+            .current_split()
+            .map_or("-".to_string(), |s| s.name().to_string()),
+        "getprevioussplitname" => {
+            previous_segment.map_or("-".to_string(), |s| s.name().to_string())
+        }
+        "getcurrenttimerphase" => phase_name(timer.current_phase()).to_string(),
+        _ => {
+            warn!("Unsupported LiveSplit Server command \"{name}\"");
+            return None;
+        }
+    };
+    Some(response)
+}
+
+/// Returns name of timer `phase`
+pub(crate) fn phase_name(phase: TimerPhase) -> &'static str {
+    match phase {
+        NotRunning => "NotRunning",
+        Running => "Running",
+        Ended => "Ended",
+        Paused => "Paused",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::timer_and_splits;
+    use livesplit_core::TimingMethod;
+
+    fn execute(
+        command: &str,
+        timer: &Arc<RwLock<Timer>>,
+        splits: &Arc<RwLock<Splits>>,
+    ) -> Option<String> {
+        execute_command(command, timer, splits, TimeFormat::default())
+    }
+
+    #[test]
+    fn commands_control_the_timer() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+        assert_eq!(execute("split", &timer, &splits), None);
+        assert_eq!(
+            execute("getcurrenttimerphase", &timer, &splits).unwrap(),
+            "NotRunning"
+        );
+        assert_eq!(execute("getsplitindex", &timer, &splits).unwrap(), "-1");
+
+        execute("starttimer", &timer, &splits);
+        execute("starttimer", &timer, &splits);
+        assert_eq!(execute("getsplitindex", &timer, &splits).unwrap(), "0");
+        assert_eq!(
+            execute("getcurrentsplitname", &timer, &splits).unwrap(),
+            "Intro"
+        );
+
+        execute("split", &timer, &splits);
+        assert_eq!(
+            execute("getprevioussplitname", &timer, &splits).unwrap(),
+            "Intro"
+        );
+        execute("unsplit", &timer, &splits);
+        assert_eq!(execute("getsplitindex", &timer, &splits).unwrap(), "0");
+
+        execute("pause", &timer, &splits);
+        assert_eq!(
+            execute("getcurrenttimerphase", &timer, &splits).unwrap(),
+            "Paused"
+        );
+        execute("resume", &timer, &splits);
+        assert_eq!(
+            execute("getcurrenttimerphase", &timer, &splits).unwrap(),
+            "Running"
+        );
+
+        execute("reset", &timer, &splits);
+        assert_eq!(
+            execute("getcurrenttimerphase", &timer, &splits).unwrap(),
+            "NotRunning"
+        );
+    }
+
+    #[test]
+    fn game_time_is_set_with_argument() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+        execute("switchto gametime", &timer, &splits);
+        execute("starttimer", &timer, &splits);
+        execute("initgametime", &timer, &splits);
+        execute("pausegametime", &timer, &splits);
+        execute("setgametime 1:02.5", &timer, &splits);
+        assert_eq!(
+            execute("getcurrenttime", &timer, &splits).unwrap(),
+            TimeFormat::default().format_time(TimeSpan::from_seconds(62.5))
+        );
+        execute("setgametime not a time", &timer, &splits);
+        assert_eq!(
+            execute("getcurrenttime", &timer, &splits).unwrap(),
+            TimeFormat::default().format_time(TimeSpan::from_seconds(62.5))
+        );
+    }
+
+    #[test]
+    fn unknown_commands_are_not_answered() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+        assert_eq!(execute("getnothing", &timer, &splits), None);
+        assert_eq!(execute("dance", &timer, &splits), None);
+        assert_eq!(
+            execute("getcurrenttimerphase", &timer, &splits).unwrap(),
+            "NotRunning"
+        );
+    }
+}
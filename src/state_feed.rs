@@ -0,0 +1,440 @@
+//! Local HTTP server publishing timer state as JSON for streaming overlays
+//! (OBS browser sources...)
+//!
+//! Endpoints:
+//!
+//! * `GET /state`: current state
+//! * `GET /events`: server-sent events stream, one `data:` event with the state
+//!   every time it changes
+//! * `POST /command`: execute livesplit server command present in request body
+//!   (`split`, `reset`, `getcurrenttime`...) and answer its response if any.
+//!   Browsers are not allowed to read its response and requests from web
+//!   pages that are not served from localhost are rejected
+use crate::server::{execute_command, phase_name};
+use crate::time_format::{format_time, TimeFormat};
+use crate::timer_controls::TimerControlError;
+use crate::ui::{Splits, UIError};
+use livesplit_core::Timer;
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// Port of state feed when not configured
+pub const DEFAULT_PORT: u16 = 16835;
+
+/// Delay between two checks for state changes of the events stream
+const EVENTS_REFRESH_DELAY: Duration = Duration::from_millis(100);
+
+/// Delay after which a client that does not send its request is disconnected
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum size in bytes of the body of a command request
+const MAX_COMMAND_LENGTH: usize = 64 * 1024;
+
+/// Snapshot of the timer sent to overlays
+#[derive(Serialize, PartialEq)]
+struct TimerState {
+    game: String,
+    category: String,
+    current_time: String,
+    phase: &'static str,
+    current_split_index: Option<usize>,
+    comparison: String,
+    attempt_count: u32,
+    splits: Vec<SplitState>,
+}
+
+/// Split row of the splits display
+#[derive(Serialize, PartialEq)]
+struct SplitState {
+    name: String,
+    time: String,
+    comparison: String,
+    time_difference: String,
+}
+
+/// Listen on localhost `port` for overlays reading the state of `timer` and
+/// `splits` display, with times formatted with `time_format`. Each client is
+/// served in its own thread
+///
+/// # Errors
+/// Returns an error if `port` cannot be listened on
+pub fn start_state_feed(
+    port: u16,
+    timer: Arc<RwLock<Timer>>,
+    splits: Arc<RwLock<Splits>>,
+    time_format: TimeFormat,
+) -> Result<(), std::io::Error> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    info!("State feed listening on port {port}");
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let timer = timer.clone();
+                    let splits = splits.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = handle_client(stream, &timer, &splits, time_format) {
+                            debug!("State feed client: {e}");
+                        }
+                    });
+                }
+                Err(e) => error!("{e}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Answer HTTP request of client `stream`
+fn handle_client(
+    mut stream: TcpStream,
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    time_format: TimeFormat,
+) -> Result<(), std::io::Error> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    let mut origin = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let mut request = request_line.split_whitespace();
+    match (request.next(), request.next()) {
+        (Some("GET"), Some("/state")) => {
+            let state = serde_json::to_string(&timer_state(timer, splits, time_format))?;
+            write_response(&mut stream, "200 OK", "application/json", &state, true)
+        }
+        (Some("GET"), Some("/events")) => stream_events(&mut stream, timer, splits, time_format),
+        (Some("POST"), Some("/command")) => {
+            if let Some(origin) = origin.filter(|o| !is_local_origin(o)) {
+                warn!("State feed: rejected command from origin \"{origin}\"");
+                return write_response(&mut stream, "403 Forbidden", "text/plain", "", false);
+            }
+            if content_length > MAX_COMMAND_LENGTH {
+                warn!("State feed: rejected command of {content_length} bytes");
+                return write_response(
+                    &mut stream,
+                    "413 Payload Too Large",
+                    "text/plain",
+                    "",
+                    false,
+                );
+            }
+            let mut command = vec![0; content_length];
+            reader.read_exact(&mut command)?;
+            let command = String::from_utf8_lossy(&command);
+            let response = command
+                .lines()
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .filter_map(|c| execute_command(c, timer, splits, time_format))
+                .collect::<Vec<String>>()
+                .join("\n");
+            write_response(&mut stream, "200 OK", "text/plain", &response, false)
+        }
+        (Some("OPTIONS"), _) => {
+            write_response(&mut stream, "204 No Content", "text/plain", "", true)
+        }
+        _ => {
+            warn!(
+                "State feed: unsupported request \"{}\"",
+                request_line.trim()
+            );
+            write_response(&mut stream, "404 Not Found", "text/plain", "", false)
+        }
+    }
+}
+
+/// Write HTTP response with `body` to `stream`. With `cors`, web pages of
+/// any origin are allowed to read it
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+    cors: bool,
+) -> Result<(), std::io::Error> {
+    let cors_headers = if cors {
+        "Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET\r\n"
+    } else {
+        ""
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+Content-Type: {content_type}\r\n\
+Content-Length: {}\r\n\
+{cors_headers}\
+Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Returns true if `origin` header value is a page served from localhost
+fn is_local_origin(origin: &str) -> bool {
+    let Some(host) = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+    else {
+        return false;
+    };
+    let host = match host.strip_prefix('[') {
+        Some(host) => host.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+/// Send state to `stream` every time it changes until client disconnects
+fn stream_events(
+    stream: &mut TcpStream,
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    time_format: TimeFormat,
+) -> Result<(), std::io::Error> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\n\
+Content-Type: text/event-stream\r\n\
+Cache-Control: no-cache\r\n\
+Access-Control-Allow-Origin: *\r\n\
+Connection: keep-alive\r\n\r\n"
+    )?;
+    let mut last_state = None;
+    loop {
+        let state = timer_state(timer, splits, time_format);
+        if last_state.as_ref() != Some(&state) {
+            let json = serde_json::to_string(&state)?;
+            write!(stream, "data: {json}\n\n")?;
+            stream.flush()?;
+            last_state = Some(state);
+        }
+        thread::sleep(EVENTS_REFRESH_DELAY);
+    }
+}
+
+/// Returns current state of `timer` and `splits` display, with current time
+/// formatted with `time_format`
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+fn timer_state(
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    time_format: TimeFormat,
+) -> TimerState {
+    let timer = match timer.read().map_err(TimerControlError::TimerReadLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let splits = match splits.read().map_err(UIError::SplitsReadLock) {
+        Ok(splits) => splits,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let run = timer.run();
+    let split_states = (0..splits.len())
+        .map(|i| SplitState {
+            name: splits.get_split_name(i),
+            time: splits.get_time(i),
+            comparison: splits.get_comparison(i),
+            time_difference: splits.get_time_difference(i),
+        })
+        .collect();
+    TimerState {
+        game: run.game_name().to_string(),
+        category: run.category_name().to_string(),
+        current_time: format_time(
+            timer.snapshot().current_time()[timer.current_timing_method()],
+            time_format,
+        ),
+        phase: phase_name(timer.current_phase()),
+        current_split_index: timer.current_split_index(),
+        comparison: timer.current_comparison().to_string(),
+        attempt_count: splits.get_attempt_count(&timer),
+        splits: split_states,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{split_at, timer_and_splits};
+    use crate::timer_controls::{set_game_time, start_or_split_timer};
+    use livesplit_core::{TimeSpan, TimerPhase, TimingMethod};
+    use serde_json::Value;
+
+    /// Returns timer using game time of a run with splits "Intro" and "End",
+    /// a personal best split at 10s and 20s, and its splits display
+    fn timer_and_splits_with_personal_best() -> (Arc<RwLock<Timer>>, Arc<RwLock<Splits>>) {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::GameTime);
+        {
+            let mut timer = timer.write().unwrap();
+            split_at(&mut timer, &[10.0, 20.0]);
+            timer.reset(true);
+        }
+        (timer, splits)
+    }
+
+    /// Returns state of `timer` and `splits` as parsed by overlays
+    fn parsed_state(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) -> Value {
+        let state = timer_state(timer, splits, TimeFormat::default());
+        serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap()
+    }
+
+    /// Returns response of state feed to `request`
+    fn respond(request: &str, timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) -> String {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        handle_client(stream, timer, splits, TimeFormat::default()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    /// Returns request executing `command`, sent from `origin` if any
+    fn command_request(command: &str, origin: Option<&str>) -> String {
+        let origin = origin.map_or(String::new(), |o| format!("Origin: {o}\r\n"));
+        format!(
+            "POST /command HTTP/1.1\r\n{origin}Content-Length: {}\r\n\r\n{command}",
+            command.len()
+        )
+    }
+
+    #[test]
+    fn state_follows_splits() {
+        let (timer, splits) = timer_and_splits_with_personal_best();
+        start_or_split_timer(&timer, &splits);
+        timer.write().unwrap().pause_game_time();
+
+        let state = parsed_state(&timer, &splits);
+        assert_eq!(state["phase"], "Running");
+        assert_eq!(state["current_split_index"], 0);
+        assert_eq!(state["comparison"], "Personal Best");
+        assert_eq!(state["attempt_count"], 2);
+        assert_eq!(state["splits"][0]["name"], "Intro");
+        assert_eq!(state["splits"][0]["comparison"], "00:00:10.000");
+        assert_eq!(state["splits"][0]["time_difference"], " 00.000");
+        assert_eq!(state["splits"][1]["comparison"], "00:00:20.000");
+
+        set_game_time(&timer, TimeSpan::from_seconds(8.5));
+        start_or_split_timer(&timer, &splits);
+
+        let state = parsed_state(&timer, &splits);
+        assert_eq!(state["phase"], "Running");
+        assert_eq!(state["current_split_index"], 1);
+        assert_eq!(state["attempt_count"], 2);
+        assert_eq!(state["splits"][0]["time"], "00:00:08.500");
+        assert_eq!(state["splits"][0]["time_difference"], "-01.500");
+        assert_eq!(state["splits"][1]["time_difference"], " 00.000");
+    }
+
+    #[test]
+    fn commands_are_executed_and_answered() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+
+        let response = respond(
+            &command_request("starttimer\n\ngetcurrenttimerphase\n", None),
+            &timer,
+            &splits,
+        );
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        assert!(response.ends_with("\r\n\r\nRunning"), "{response}");
+        let response = respond("GET /state HTTP/1.1\r\n\r\n", &timer, &splits);
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let state: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(state["phase"], "Running");
+        assert_eq!(state["current_split_index"], 0);
+    }
+
+    #[test]
+    fn commands_of_foreign_pages_are_rejected() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+
+        let response = respond(
+            &command_request("starttimer", Some("https://example.com")),
+            &timer,
+            &splits,
+        );
+
+        assert!(
+            response.starts_with("HTTP/1.1 403 Forbidden\r\n"),
+            "{response}"
+        );
+        assert_eq!(
+            timer.read().unwrap().current_phase(),
+            TimerPhase::NotRunning
+        );
+        let response = respond(
+            &command_request("starttimer", Some("http://localhost:8080")),
+            &timer,
+            &splits,
+        );
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        assert_eq!(timer.read().unwrap().current_phase(), TimerPhase::Running);
+    }
+
+    #[test]
+    fn oversized_commands_are_rejected() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+        let request = format!(
+            "POST /command HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_COMMAND_LENGTH + 1
+        );
+
+        let response = respond(&request, &timer, &splits);
+
+        assert!(
+            response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"),
+            "{response}"
+        );
+        assert_eq!(
+            timer.read().unwrap().current_phase(),
+            TimerPhase::NotRunning
+        );
+    }
+
+    #[test]
+    fn local_origins_are_accepted() {
+        assert!(is_local_origin("http://localhost"));
+        assert!(is_local_origin("http://localhost:8080"));
+        assert!(is_local_origin("https://127.0.0.1:16835"));
+        assert!(is_local_origin("http://[::1]:3000"));
+    }
+
+    #[test]
+    fn foreign_origins_are_rejected() {
+        assert!(!is_local_origin("https://example.com"));
+        assert!(!is_local_origin("http://localhost.example.com"));
+        assert!(!is_local_origin("http://127.0.0.1.example.com"));
+        assert!(!is_local_origin("null"));
+        assert!(!is_local_origin("file://"));
+    }
+}
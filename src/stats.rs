@@ -0,0 +1,269 @@
+//! Statistics of past attempts computed from the attempt history and segment
+//! history of a run
+use crate::time_format::TimeFormat;
+use livesplit_core::{Run, TimeSpan, TimingMethod};
+
+/// Width of the longest bar of the reset histogram
+const HISTOGRAM_WIDTH: usize = 20;
+
+/// Statistics of all attempts of a run
+pub struct RunStatistics {
+    attempt_count: usize,
+    completed_count: usize,
+    /// attempt index and final time of every new personal best
+    personal_best_progression: Vec<(i32, TimeSpan)>,
+    segments: Vec<SegmentStatistics>,
+}
+
+/// Statistics of one segment across all attempts
+struct SegmentStatistics {
+    name: String,
+    best: Option<TimeSpan>,
+    worst: Option<TimeSpan>,
+    average: Option<TimeSpan>,
+    median: Option<TimeSpan>,
+    /// number of attempts reset during this segment
+    resets: usize,
+}
+
+impl RunStatistics {
+    /// Compute statistics of `run` with `timing_method`
+    #[must_use]
+    pub fn new(run: &Run, timing_method: TimingMethod) -> RunStatistics {
+        let attempts = run.attempt_history();
+        let mut personal_best_progression: Vec<(i32, TimeSpan)> = vec![];
+        for attempt in attempts {
+            if let Some(time) = attempt.time()[timing_method] {
+                if personal_best_progression
+                    .last()
+                    .is_none_or(|(_, pb)| time < *pb)
+                {
+                    personal_best_progression.push((attempt.index(), time));
+                }
+            }
+        }
+
+        let mut resets = vec![0; run.len()];
+        // NOTE: segments after the reset point have no history for the attempt
+        for attempt in attempts
+            .iter()
+            .filter(|a| a.time()[timing_method].is_none())
+        {
+            if let Some(i) = run
+                .segments()
+                .iter()
+                .position(|s| s.segment_history().get(attempt.index()).is_none())
+            {
+                resets[i] += 1;
+            }
+        }
+
+        let segments = run
+            .segments()
+            .iter()
+            .zip(resets)
+            .map(|(segment, resets)| {
+                let mut times: Vec<TimeSpan> = segment
+                    .segment_history()
+                    .iter_actual_runs()
+                    .filter_map(|(_, time)| time[timing_method])
+                    .collect();
+                times.sort();
+                SegmentStatistics {
+                    name: segment.name().to_string(),
+                    best: segment.best_segment_time()[timing_method],
+                    worst: times.last().copied(),
+                    average: average(&times),
+                    median: median(&times),
+                    resets,
+                }
+            })
+            .collect();
+
+        RunStatistics {
+            attempt_count: attempts.len(),
+            completed_count: attempts
+                .iter()
+                .filter(|a| a.time()[timing_method].is_some())
+                .count(),
+            personal_best_progression,
+            segments,
+        }
+    }
+
+    /// Returns percentage of attempts that were completed
+    #[must_use]
+    pub fn completion_rate(&self) -> f64 {
+        if self.attempt_count == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let rate = self.completed_count as f64 / self.attempt_count as f64 * 100.0;
+        rate
+    }
+
+    /// Returns statistics as lines of text, times formatted with `time_format`
+    #[must_use]
+    pub fn report(&self, time_format: TimeFormat) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Attempts: {} ({} completed, {:.1}%)",
+            self.attempt_count,
+            self.completed_count,
+            self.completion_rate()
+        )];
+
+        lines.push(String::new());
+        lines.push("Personal best progression:".to_string());
+        if self.personal_best_progression.is_empty() {
+            lines.push("  no completed attempt".to_string());
+        }
+        for (index, time) in &self.personal_best_progression {
+            let time = time_format.format_time(*time);
+            lines.push(format!("  #{index:<5} {time}"));
+        }
+
+        let padding = self
+            .segments
+            .iter()
+            .map(|s| s.name.len())
+            .chain(["Segment".len()])
+            .max()
+            .unwrap_or(0);
+        let max_resets = self.segments.iter().map(|s| s.resets).max().unwrap_or(0);
+        lines.push(String::new());
+        lines.push(format!(
+            "{:<padding$} {:>12} {:>12} {:>12} {:>12} Resets",
+            "Segment", "Best", "Average", "Median", "Worst"
+        ));
+        for segment in &self.segments {
+            let bar_length = (segment.resets * HISTOGRAM_WIDTH)
+                .checked_div(max_resets)
+                .unwrap_or(0);
+            lines.push(format!(
+                "{:<padding$} {:>12} {:>12} {:>12} {:>12} {:>5} {}",
+                segment.name,
+                format_segment_time(segment.best, time_format),
+                format_segment_time(segment.average, time_format),
+                format_segment_time(segment.median, time_format),
+                format_segment_time(segment.worst, time_format),
+                segment.resets,
+                "#".repeat(bar_length)
+            ));
+        }
+        lines
+    }
+}
+
+/// Returns average of `times`
+fn average(times: &[TimeSpan]) -> Option<TimeSpan> {
+    if times.is_empty() {
+        return None;
+    }
+    let total: f64 = times.iter().map(TimeSpan::total_seconds).sum();
+    #[allow(clippy::cast_precision_loss)]
+    let average = total / times.len() as f64;
+    Some(TimeSpan::from_seconds(average))
+}
+
+/// Returns median of sorted `times`, average of the two middle times for an
+/// even number of times
+fn median(times: &[TimeSpan]) -> Option<TimeSpan> {
+    let middle = times.len() / 2;
+    if times.len() % 2 == 1 {
+        times.get(middle).copied()
+    } else {
+        average(times.get(middle.checked_sub(1)?..=middle)?)
+    }
+}
+
+/// Formats segment `time` with `time_format` or "-" if absent
+fn format_segment_time(time: Option<TimeSpan>, time_format: TimeFormat) -> String {
+    time.map_or_else(|| "-".to_string(), |time| time_format.format_timesave(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{split_at, timer};
+
+    /// Returns run with segments "Intro" and "End" after attempts split at
+    /// game times in `attempts` (reset before the end if times are missing).
+    /// Game time is not initialized for attempts without split times
+    fn run_with_attempts(attempts: &[&[f64]]) -> Run {
+        let mut timer = timer(&["Intro", "End"], TimingMethod::RealTime);
+        for split_times in attempts {
+            if split_times.is_empty() {
+                timer.start();
+                timer.split();
+                timer.split();
+            } else {
+                split_at(&mut timer, split_times);
+            }
+            timer.reset(true);
+        }
+        timer.run().clone()
+    }
+
+    #[test]
+    fn attempts_are_counted_with_timing_method() {
+        let run = run_with_attempts(&[&[40.0, 100.0], &[30.0], &[]]);
+
+        let game_time = RunStatistics::new(&run, TimingMethod::GameTime);
+        assert_eq!(game_time.attempt_count, 3);
+        assert_eq!(game_time.completed_count, 1);
+
+        let real_time = RunStatistics::new(&run, TimingMethod::RealTime);
+        assert_eq!(real_time.completed_count, 2);
+        assert!((real_time.completion_rate() - 200.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn personal_best_progression_keeps_improvements() {
+        let run = run_with_attempts(&[&[40.0, 100.0], &[30.0], &[35.0, 90.0], &[35.0, 95.0]]);
+
+        let stats = RunStatistics::new(&run, TimingMethod::GameTime);
+
+        let progression: Vec<f64> = stats
+            .personal_best_progression
+            .iter()
+            .map(|(_, time)| time.total_seconds())
+            .collect();
+        assert_eq!(progression, [100.0, 90.0]);
+        assert!(stats.personal_best_progression[0].0 < stats.personal_best_progression[1].0);
+    }
+
+    #[test]
+    fn segments_count_resets_and_times() {
+        let run = run_with_attempts(&[&[40.0, 100.0], &[30.0], &[35.0, 90.0], &[35.0, 95.0]]);
+
+        let stats = RunStatistics::new(&run, TimingMethod::GameTime);
+
+        let resets: Vec<usize> = stats.segments.iter().map(|s| s.resets).collect();
+        assert_eq!(resets, [0, 1]);
+        let intro = &stats.segments[0];
+        assert_eq!(intro.best, Some(TimeSpan::from_seconds(30.0)));
+        assert_eq!(intro.worst, Some(TimeSpan::from_seconds(40.0)));
+        assert_eq!(intro.median, Some(TimeSpan::from_seconds(35.0)));
+        assert_eq!(intro.average, Some(TimeSpan::from_seconds(35.0)));
+    }
+
+    #[test]
+    fn median_of_even_number_of_times_is_between_middle_times() {
+        let run = run_with_attempts(&[&[40.0, 100.0], &[30.0, 95.0]]);
+
+        let stats = RunStatistics::new(&run, TimingMethod::GameTime);
+
+        assert_eq!(stats.segments[0].median, Some(TimeSpan::from_seconds(35.0)));
+        assert_eq!(stats.segments[1].median, Some(TimeSpan::from_seconds(62.5)));
+        assert_eq!(median(&[]), None);
+    }
+
+    #[test]
+    fn run_without_attempts_has_no_statistics() {
+        let stats = RunStatistics::new(&run_with_attempts(&[]), TimingMethod::RealTime);
+        assert!(stats.completion_rate().abs() < f64::EPSILON);
+        assert!(stats
+            .report(TimeFormat::default())
+            .contains(&"  no completed attempt".to_string()));
+    }
+}
@@ -0,0 +1,209 @@
+//! Text files with timer data for streaming software ("read from file" text
+//! sources of OBS...), lighter alternative to the state feed
+//!
+//! Each selected output is written to `<output>.txt` in the outputs folder of
+//! the data folder and rewritten only when its content changes
+use crate::persistence::write_atomically;
+use crate::time_format::{format_delta, format_time, TimeFormat};
+use crate::timer_controls::TimerControlError;
+use crate::ui::Splits;
+use livesplit_core::analysis::{delta, sum_of_segments};
+use livesplit_core::Timer;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// Delay between two updates of text files
+const REFRESH_DELAY: Duration = Duration::from_millis(200);
+
+/// Timer data that can be written to a text file
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TextOutput {
+    /// Time of the timer
+    CurrentTime,
+    /// Name of current split
+    CurrentSplit,
+    /// Delta of current split against current comparison
+    Delta,
+    /// Final time of personal best
+    PersonalBest,
+    /// Sum of best segments
+    SumOfBest,
+    /// Number of attempts
+    AttemptCount,
+}
+
+impl TextOutput {
+    /// All outputs, in the order they are documented
+    pub const ALL: [TextOutput; 6] = [
+        TextOutput::CurrentTime,
+        TextOutput::CurrentSplit,
+        TextOutput::Delta,
+        TextOutput::PersonalBest,
+        TextOutput::SumOfBest,
+        TextOutput::AttemptCount,
+    ];
+
+    /// Name of output, also used as file name without extension
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            TextOutput::CurrentTime => "current_time",
+            TextOutput::CurrentSplit => "current_split",
+            TextOutput::Delta => "delta",
+            TextOutput::PersonalBest => "personal_best",
+            TextOutput::SumOfBest => "sum_of_best",
+            TextOutput::AttemptCount => "attempt_count",
+        }
+    }
+
+    /// Returns output named `name`
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<TextOutput> {
+        TextOutput::ALL.into_iter().find(|o| o.name() == name)
+    }
+}
+
+/// Write `outputs` of `timer` and `splits` display, formatted with
+/// `time_format`, into `folder` at a fixed cadence. Folder is created if
+/// missing
+///
+/// # Errors
+/// Returns an error if `folder` cannot be created
+pub fn start_text_outputs(
+    folder: PathBuf,
+    outputs: Vec<TextOutput>,
+    timer: Arc<RwLock<Timer>>,
+    splits: Arc<RwLock<Splits>>,
+    time_format: TimeFormat,
+) -> Result<(), std::io::Error> {
+    fs::create_dir_all(&folder)?;
+    info!("Writing text outputs to \"{}\"", folder.display());
+    thread::spawn(move || {
+        let mut last_values: HashMap<TextOutput, String> = HashMap::new();
+        loop {
+            let values = output_values(&timer, &splits, &outputs, time_format);
+            for (output, value) in values {
+                if last_values.get(&output) == Some(&value) {
+                    continue;
+                }
+                let path = folder.join(format!("{}.txt", output.name()));
+                // NOTE: overlays never read a truncated file
+                match write_atomically(&path, value.as_bytes()) {
+                    Ok(()) => {
+                        last_values.insert(output, value);
+                    }
+                    Err(e) => error!("\"{}\": {e}", path.display()),
+                }
+            }
+            thread::sleep(REFRESH_DELAY);
+        }
+    });
+    Ok(())
+}
+
+/// Returns current value of `outputs` of `timer` and `splits` display
+/// formatted with `time_format`
+///
+/// # Panics
+/// Panics if timer or splits lock cannot be acquired
+fn output_values(
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    outputs: &[TextOutput],
+    time_format: TimeFormat,
+) -> Vec<(TextOutput, String)> {
+    let timer = match timer.read().map_err(TimerControlError::TimerReadLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let attempt_count = match splits.read().map_err(TimerControlError::SplitsReadLock) {
+        Ok(splits) => splits.get_attempt_count(&timer),
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let timing_method = timer.current_timing_method();
+    let run = timer.run();
+    outputs
+        .iter()
+        .map(|output| {
+            let value = match output {
+                TextOutput::CurrentTime => {
+                    format_time(timer.snapshot().current_time()[timing_method], time_format)
+                }
+                TextOutput::CurrentSplit => timer
+                    .current_split()
+                    .map_or_else(|| "-".to_string(), |s| s.name().to_string()),
+                TextOutput::Delta => {
+                    let (delta, _) =
+                        delta::calculate(&timer.snapshot(), timer.current_comparison());
+                    format_delta(delta, time_format)
+                }
+                TextOutput::PersonalBest => format_time(
+                    run.segments()
+                        .last()
+                        .and_then(|s| s.personal_best_split_time()[timing_method]),
+                    time_format,
+                ),
+                TextOutput::SumOfBest => format_time(
+                    sum_of_segments::calculate_best(run.segments(), false, true, timing_method),
+                    time_format,
+                ),
+                TextOutput::AttemptCount => attempt_count.to_string(),
+            };
+            (*output, value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::timer_and_splits;
+    use livesplit_core::TimingMethod;
+
+    #[test]
+    fn names_match_configuration_values() {
+        for output in TextOutput::ALL {
+            assert_eq!(TextOutput::from_name(output.name()), Some(output));
+            assert_eq!(
+                serde_json::to_string(&output).unwrap(),
+                format!("\"{}\"", output.name())
+            );
+        }
+        assert_eq!(TextOutput::from_name("current time"), None);
+    }
+
+    #[test]
+    fn values_follow_the_timer() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+        let outputs = [
+            TextOutput::CurrentSplit,
+            TextOutput::PersonalBest,
+            TextOutput::AttemptCount,
+        ];
+        let values = |timer| output_values(timer, &splits, &outputs, TimeFormat::default());
+
+        let expected = |split: &str, attempts: &str| {
+            vec![
+                (TextOutput::CurrentSplit, split.to_string()),
+                (TextOutput::PersonalBest, "-".to_string()),
+                (TextOutput::AttemptCount, attempts.to_string()),
+            ]
+        };
+        assert_eq!(values(&timer), expected("-", "0"));
+        timer.write().unwrap().start();
+        assert_eq!(values(&timer), expected("Intro", "1"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Formatting of times shown in the UI and written to every output (livesplit
+//! server, state feed, text files, statistics)
+use livesplit_core::TimeSpan;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+/// Highest number of displayed decimals of seconds (milliseconds)
+const MAX_DECIMALS: u8 = 3;
+
+/// Format of displayed times. Missing settings format times as
+/// "hh:mm:ss.mmm"
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(default)]
+pub struct TimeFormat {
+    /// Number of decimals of seconds, from 0 to 3
+    decimals: u8,
+    /// Roll hours over into days ("1d 02:03:04.000") instead of counting hours
+    /// past 24
+    days: bool,
+    /// Hide hours and minutes when they are 0
+    hide_leading_zeros: bool,
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        TimeFormat {
+            decimals: MAX_DECIMALS,
+            days: true,
+            hide_leading_zeros: false,
+        }
+    }
+}
+
+impl TimeFormat {
+    /// Formats `time`. Negative times are prefixed with '-'
+    #[must_use]
+    pub fn format_time(self, time: TimeSpan) -> String {
+        let sign = if time < TimeSpan::zero() { "-" } else { "" };
+        format!(
+            "{sign}{}",
+            self.format_fields(time, self.hide_leading_zeros)
+        )
+    }
+
+    /// Formats absolute value of `time` without hours and minutes when they
+    /// are 0
+    #[must_use]
+    pub fn format_timesave(self, time: TimeSpan) -> String {
+        self.format_fields(time, true)
+    }
+
+    /// Formats `time` like [`TimeFormat::format_timesave`] prefixed with its
+    /// sign ('-' indicates a timesave)
+    #[must_use]
+    pub fn format_delta(self, time: TimeSpan) -> String {
+        let sign = match time.partial_cmp(&TimeSpan::zero()) {
+            Some(Ordering::Less) => '-',
+            Some(Ordering::Greater) => '+',
+            _ => ' ',
+        };
+        format!("{sign}{}", self.format_timesave(time))
+    }
+
+    /// Formats absolute value of `time`. The first displayed field is always
+    /// padded to two digits
+    fn format_fields(self, time: TimeSpan, hide_leading_zeros: bool) -> String {
+        let decimals = self.decimals.min(MAX_DECIMALS);
+        let milliseconds = time.to_duration().whole_milliseconds().unsigned_abs();
+        let seconds = milliseconds / 1000;
+        let (days, hours) = if self.days {
+            (seconds / 86400, seconds / 3600 % 24)
+        } else {
+            (0, seconds / 3600)
+        };
+        let minutes = seconds / 60 % 60;
+
+        let mut text = String::new();
+        // NOTE: writing to a String cannot fail
+        if days > 0 {
+            let _ = write!(text, "{days}d {hours:02}:{minutes:02}:");
+        } else if hours > 0 || !hide_leading_zeros {
+            let _ = write!(text, "{hours:02}:{minutes:02}:");
+        } else if minutes > 0 {
+            let _ = write!(text, "{minutes:02}:");
+        }
+        let _ = write!(text, "{:02}", seconds % 60);
+        if decimals > 0 {
+            // truncate like livesplit instead of rounding
+            let fraction = milliseconds % 1000 / 10u128.pow(u32::from(MAX_DECIMALS - decimals));
+            let _ = write!(text, ".{fraction:0width$}", width = usize::from(decimals));
+        }
+        text
+    }
+}
+
+/// Formats `time` with `time_format` or "-" if absent
+pub(crate) fn format_time(time: Option<TimeSpan>, time_format: TimeFormat) -> String {
+    time.map_or_else(|| "-".to_string(), |time| time_format.format_time(time))
+}
+
+/// Formats signed `delta` with `time_format` or "-" if absent
+pub(crate) fn format_delta(delta: Option<TimeSpan>, time_format: TimeFormat) -> String {
+    delta.map_or_else(|| "-".to_string(), |delta| time_format.format_delta(delta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(decimals: u8, days: bool, hide_leading_zeros: bool, seconds: f64) -> String {
+        let time_format = TimeFormat {
+            decimals,
+            days,
+            hide_leading_zeros,
+        };
+        time_format.format_time(TimeSpan::from_seconds(seconds))
+    }
+
+    #[test]
+    fn hours_roll_over_into_days() {
+        let time = 26.0 * 3600.0 + 3.0 * 60.0 + 4.5;
+        assert_eq!(format(3, true, false, time), "1d 02:03:04.500");
+        assert_eq!(format(3, false, false, time), "26:03:04.500");
+        assert_eq!(format(3, true, true, time), "1d 02:03:04.500");
+        assert_eq!(format(0, true, false, 86400.0), "1d 00:00:00");
+        assert_eq!(format(0, true, false, 86399.0), "23:59:59");
+    }
+
+    #[test]
+    fn decimals_are_truncated() {
+        let time = 61.987;
+        assert_eq!(format(3, true, false, time), "00:01:01.987");
+        assert_eq!(format(2, true, false, time), "00:01:01.98");
+        assert_eq!(format(1, true, false, time), "00:01:01.9");
+        assert_eq!(format(0, true, false, time), "00:01:01");
+        assert_eq!(format(9, true, false, time), "00:01:01.987");
+    }
+
+    #[test]
+    fn leading_zeros_are_hidden() {
+        assert_eq!(format(1, true, true, 5.25), "05.2");
+        assert_eq!(format(1, true, true, 65.25), "01:05.2");
+        assert_eq!(format(1, true, true, 3605.25), "01:00:05.2");
+        assert_eq!(format(1, true, false, -5.25), "-00:00:05.2");
+        assert_eq!(format(1, true, true, -5.25), "-05.2");
+    }
+
+    #[test]
+    fn deltas_are_signed() {
+        let time_format = TimeFormat::default();
+        assert_eq!(
+            time_format.format_delta(TimeSpan::from_seconds(-1.5)),
+            "-01.500"
+        );
+        assert_eq!(
+            time_format.format_delta(TimeSpan::from_seconds(61.5)),
+            "+01:01.500"
+        );
+        assert_eq!(time_format.format_delta(TimeSpan::zero()), " 00.000");
+    }
+
+    #[test]
+    fn absent_times_are_dashed() {
+        let time_format = TimeFormat::default();
+        assert_eq!(format_time(None, time_format), "-");
+        assert_eq!(format_delta(None, time_format), "-");
+        assert_eq!(
+            format_delta(Some(TimeSpan::zero()), time_format),
+            time_format.format_delta(TimeSpan::zero())
+        );
+    }
+}
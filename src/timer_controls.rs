@@ -1,9 +1,13 @@
 //! Control the timer with keybinding and its callback functions
+use crate::persistence::{save_run_to_file, SpeedrunSettings};
+use crate::segment_practice::SegmentPractice;
 use crate::ui::Splits;
-use livesplit_core::TimeSpan;
+use livesplit_core::analysis;
 use livesplit_core::Timer;
 use livesplit_core::TimerPhase::{Ended, NotRunning};
-use log::{error, info};
+use livesplit_core::TimingMethod;
+use livesplit_core::{AtomicDateTime, Time, TimeSpan};
+use log::{error, info, warn};
 use std::fmt;
 use std::fmt::Debug;
 use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
@@ -34,9 +38,16 @@ impl fmt::Display for TimerControlError<'_> {
 
 /// Starts `timer`, logs keypress and update `splits` display
 ///
+/// In segment practice, the timer starts at the first practiced split,
+/// practiced segment times are logged and the timer is paused after the last
+/// practiced split
+///
 /// # Panics
 /// Panics if any errors occur with the timer or the splits display
 pub fn start_or_split_timer(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
+    if keep_best_segments(timer, splits) {
+        return;
+    }
     let message = match timer.read().map_err(TimerControlError::TimerReadLock) {
         Ok(timer) => match timer.current_phase() {
             NotRunning => "Start/split keypress: start",
@@ -51,7 +62,30 @@ pub fn start_or_split_timer(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Spli
         info!("{message}");
     }
     match timer.write().map_err(TimerControlError::TimerWriteLock) {
-        Ok(mut timer) => timer.split_or_start(),
+        Ok(mut timer) => {
+            let segment_practice = match splits.read().map_err(TimerControlError::SplitsReadLock) {
+                Ok(splits) => splits.get_segment_practice().cloned(),
+                Err(e) => {
+                    error!("{e}");
+                    panic!("{e}") // cannot recover
+                }
+            };
+            let split_index = timer.current_split_index();
+            if let Some(practice) = &segment_practice {
+                if split_index.is_some_and(|i| i > practice.get_end()) {
+                    info!("Practiced segments done, reset to practice again");
+                    return;
+                }
+            }
+            timer.split_or_start();
+            // game time follows real time until it is paused or set
+            if !message.is_empty() {
+                timer.initialize_game_time();
+            }
+            if let Some(practice) = &segment_practice {
+                follow_segment_practice(&mut timer, practice, !message.is_empty(), split_index);
+            }
+        }
         Err(e) => {
             error!("{e}");
             panic!("{e}") // cannot recover
@@ -61,9 +95,10 @@ pub fn start_or_split_timer(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Spli
         Ok(timer) => {
             let snapshot = timer.snapshot();
             let segments = snapshot.run().segments();
+            let timing_method = timer.current_timing_method();
             for (i, segment) in segments.iter().enumerate() {
                 let comparison = timer.current_comparison();
-                let comparison = match segment.comparison(comparison).real_time {
+                let comparison = match segment.comparison(comparison)[timing_method] {
                     Some(ts) => ts,
                     None => TimeSpan::default(),
                 };
@@ -75,12 +110,19 @@ pub fn start_or_split_timer(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Spli
                             panic!("{e}") // cannot recover
                         }
                     };
-                if let Some(time) = segment.split_time().real_time {
+                if let Some(time) = segment.split_time()[timing_method] {
                     splits_write.update_split(i, time, comparison);
                 } else {
                     splits_write.update_split(i, TimeSpan::zero(), comparison);
                 }
             }
+            match splits.write().map_err(TimerControlError::SplitsWriteLock) {
+                Ok(mut splits) => splits.update_analysis(&timer),
+                Err(e) => {
+                    error!("{e}");
+                    panic!("{e}") // cannot recover
+                }
+            }
 
             // if timer was started, don't check for splits or speedrun end
             if message.is_empty() {
@@ -98,8 +140,139 @@ pub fn start_or_split_timer(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Spli
     };
 }
 
+/// Move `timer` to the first practiced split of `practice` if it was
+/// `started`. Otherwise, log the segment time of split `split_index` and pause
+/// after the last practiced split
+fn follow_segment_practice(
+    timer: &mut Timer,
+    practice: &SegmentPractice,
+    started: bool,
+    split_index: Option<usize>,
+) {
+    if started {
+        for _ in 0..practice.get_start() {
+            timer.skip_split();
+        }
+    } else if let Some(i) = split_index.filter(|i| {
+        // split keypress is ignored while paused
+        practice.contains(*i) && timer.current_split_index() != split_index
+    }) {
+        if let Err(e) = practice.record(timer, i) {
+            error!("{e}");
+        }
+        if i == practice.get_end() && timer.current_phase() != Ended {
+            timer.pause();
+        }
+    }
+}
+
+/// Reset `timer` keeping best segments if the user was asked whether to keep
+/// them. Returns true if the user was asked
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+fn keep_best_segments(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) -> bool {
+    // NOTE: write locks are only taken when the user is asked, so that split
+    //       keypresses do not wait for the display
+    match splits.read().map_err(TimerControlError::SplitsReadLock) {
+        Ok(splits) if !splits.is_asking_to_save_golds() => return false,
+        Ok(_) => {}
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    }
+    let mut timer = match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let mut splits = match splits.write().map_err(TimerControlError::SplitsWriteLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    // the reset key may have answered in the meantime
+    if !splits.is_asking_to_save_golds() {
+        return false;
+    }
+    info!("Best segments kept");
+    reset_timer(&mut timer, &mut splits, true);
+    true
+}
+
+/// Undo last split of `timer` and roll back `splits` display
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub fn undo_split(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
+    info!("Undo split keypress");
+    let mut timer = match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    timer.undo_split();
+    update_splits_display(&timer, splits);
+}
+
+/// Skip current split of `timer` and blank it in `splits` display
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub fn skip_split(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
+    info!("Skip split keypress");
+    let mut timer = match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    timer.skip_split();
+    update_splits_display(&timer, splits);
+}
+
+/// Update every split of `splits` display with split times of `timer`. Splits
+/// without split time (not reached, undone or skipped) are blanked
+///
+/// # Panics
+/// Panics if any errors occur with the splits display
+fn update_splits_display(timer: &Timer, splits: &Arc<RwLock<Splits>>) {
+    let mut splits = match splits.write().map_err(TimerControlError::SplitsWriteLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let comparison = timer.current_comparison();
+    let timing_method = timer.current_timing_method();
+    for (i, segment) in timer.run().segments().iter().enumerate() {
+        let comparison = segment.comparison(comparison)[timing_method].unwrap_or_default();
+        match segment.split_time()[timing_method] {
+            Some(time) => splits.update_split(i, time, comparison),
+            None => splits.update_split(i, TimeSpan::zero(), comparison),
+        }
+        // comparison might be missing for the selected timing method
+        splits.refresh_splits(i, comparison);
+    }
+    splits.update_analysis(timer);
+}
+
 /// Reset `timer` (which adds one attempt) and clear `splits` time display
 ///
+/// Depending on the reset policy of `splits`, the reset key has to be pressed
+/// twice and improved best segments of an unfinished attempt are only kept if
+/// the user confirms it with the split key. Pressing the reset key while asked
+/// discards them. In practice mode, the attempt is not recorded
+///
 /// # Panics
 /// Panics if any errors occur with the timer or the splits display
 pub fn reset(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
@@ -111,9 +284,52 @@ pub fn reset(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
             panic!("{e}") // cannot recover
         }
     };
-    timer.reset(true);
+    let mut splits = match splits.write().map_err(TimerControlError::SplitsWriteLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    if splits.is_asking_to_save_golds() {
+        info!("Best segments discarded");
+        reset_timer(&mut timer, &mut splits, false);
+        return;
+    }
+    if timer.current_phase() != NotRunning {
+        let policy = splits.get_reset_policy();
+        if policy.double_press && !splits.confirm_reset() {
+            info!("Press reset key again to reset");
+            return;
+        }
+        if policy.ask_to_save_golds
+            && !splits.is_practicing()
+            && !splits.is_resumed_attempt()
+            && timer.current_phase() != Ended
+            && has_new_best_segments(&timer)
+        {
+            info!("Best segments improved: split key keeps them, reset key discards them");
+            splits.ask_to_save_golds();
+            return;
+        }
+    }
+    reset_timer(&mut timer, &mut splits, true);
+}
 
-    // clear display
+/// Reset `timer` without any confirmation, keeping improved best segments,
+/// and clear `splits` time display. In practice mode, the attempt is not
+/// recorded
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub fn reset_without_confirmation(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
+    let mut timer = match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
     let mut splits = match splits.write().map_err(TimerControlError::SplitsWriteLock) {
         Ok(m) => m,
         Err(e) => {
@@ -121,18 +337,67 @@ pub fn reset(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
             panic!("{e}") // cannot recover
         }
     };
+    reset_timer(&mut timer, &mut splits, true);
+}
+
+/// Reset `timer` and clear `splits` time display. Attempt history, best
+/// segments and personal best are only updated with `update_splits` outside
+/// of practice mode. A resumed attempt is only added to the attempt history
+fn reset_timer(timer: &mut Timer, splits: &mut Splits, update_splits: bool) {
+    splits.clear_reset_confirmations();
+    let resumed_attempt_run_offset = splits.take_run_offset();
+    if splits.is_practicing() {
+        let attempt_in_progress = timer.current_phase() != NotRunning;
+        timer.reset(false);
+        if attempt_in_progress {
+            // NOTE: starting the attempt counted it
+            splits.uncount_practice_attempt();
+        }
+    } else if let Some(run_offset) = resumed_attempt_run_offset {
+        // NOTE: splits completed before the interruption were skipped, so
+        // best segments, segment history and personal best are not updated
+        let pause_time = timer.get_pause_time();
+        timer.reset(false);
+        let mut run = timer.run().clone();
+        run.set_offset(run_offset);
+        run.add_attempt(
+            Time::default(),
+            None,
+            Some(AtomicDateTime::now()),
+            pause_time,
+        );
+        if timer.set_run(run).is_err() {
+            error!("Resumed attempt could not be added to the attempt history");
+        }
+        info!("Resumed attempt added to the attempt history as a reset");
+    } else {
+        timer.reset(update_splits);
+    }
+
+    // clear display
 
     // Update comparison time
     let run = timer.run();
     let comparison = timer.current_comparison();
+    let timing_method = timer.current_timing_method();
     for (i, segment) in run.segments().iter().enumerate() {
-        let comparison = match segment.comparison(comparison).real_time {
+        let comparison = match segment.comparison(comparison)[timing_method] {
             Some(ts) => ts,
             None => TimeSpan::default(),
         };
         splits.update_split(i, TimeSpan::zero(), comparison);
     }
     splits.clear_time_differences();
+    splits.update_analysis(timer);
+}
+
+/// Returns true if any completed segment of the current attempt of `timer` is
+/// a new best segment
+fn has_new_best_segments(timer: &Timer) -> bool {
+    let timing_method = timer.current_timing_method();
+    let completed = timer.current_split_index().unwrap_or(0);
+    (0..completed.min(timer.run().len()))
+        .any(|i| analysis::check_best_segment(timer, i, timing_method))
 }
 
 /// Pause `timer`
@@ -171,6 +436,29 @@ pub fn unpause(timer: &Arc<RwLock<Timer>>) {
     }
 }
 
+/// Pause game time of `timer` (load removal) if it is running, resume it
+/// otherwise. Real time keeps running
+///
+/// # Panics
+/// Panics if any errors occur with the timer
+pub fn toggle_game_time_pause(timer: &Arc<RwLock<Timer>>) {
+    match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(mut timer) => {
+            if timer.is_game_time_paused() {
+                info!("game time resumed");
+                timer.resume_game_time();
+            } else {
+                info!("game time paused");
+                timer.pause_game_time();
+            }
+        }
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    }
+}
+
 /// Switch to next comparison
 ///
 /// # Panics
@@ -188,9 +476,10 @@ pub fn switch_comparison(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>
 
     let snapshot = timer.snapshot();
     let segments = snapshot.run().segments();
+    let timing_method = timer.current_timing_method();
     for (i, segment) in segments.iter().enumerate() {
         let comparison = timer.current_comparison();
-        let comparison = match segment.comparison(comparison).real_time {
+        let comparison = match segment.comparison(comparison)[timing_method] {
             Some(ts) => ts,
             None => TimeSpan::default(),
         };
@@ -203,4 +492,411 @@ pub fn switch_comparison(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>
         };
         splits_write.refresh_splits(i, comparison);
     }
+    match splits.write().map_err(TimerControlError::SplitsWriteLock) {
+        Ok(mut splits) => splits.update_analysis(&timer),
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    }
+}
+
+/// Toggle between real time and game time of `timer` and update `splits`
+/// display with times of the selected timing method
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub fn toggle_timing_method(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
+    let mut timer = match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    timer.toggle_timing_method();
+    info!(
+        "Switching timing method to {:?}",
+        timer.current_timing_method()
+    );
+    update_splits_display(&timer, splits);
+}
+
+/// Toggle practice mode, where attempts of `timer` are not recorded and the
+/// run file of speedrun `settings` is never written. The run is saved before
+/// entering practice mode. Ignored while an attempt is in progress
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub fn toggle_practice(
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    settings: &SpeedrunSettings,
+) {
+    let timer = match timer.read().map_err(TimerControlError::TimerReadLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    if timer.current_phase() != NotRunning {
+        warn!("Reset the timer before toggling practice mode");
+        return;
+    }
+    let mut splits = match splits.write().map_err(TimerControlError::SplitsWriteLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    if splits.get_segment_practice().is_some() {
+        warn!("Segment practice always runs in practice mode");
+        return;
+    }
+    let practice = !splits.is_practicing();
+    if practice {
+        if let Err(e) = save_run_to_file(&splits.get_saved_run(&timer), settings) {
+            error!("{e}");
+            return;
+        }
+        info!("Saved run");
+    }
+    splits.set_practice(practice);
+    info!("Practice mode {}", if practice { "on" } else { "off" });
+}
+
+/// Switch `splits` display between cumulative split times and segment times
+///
+/// # Panics
+/// Panics if any errors occur with the splits display
+pub fn toggle_segment_times(splits: &Arc<RwLock<Splits>>) {
+    let mut splits = match splits.write().map_err(TimerControlError::SplitsWriteLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    splits.toggle_segment_times();
+    info!(
+        "{} times shown",
+        if splits.is_showing_segment_times() {
+            "Segment"
+        } else {
+            "Split"
+        }
+    );
+}
+
+/// Set current comparison of `timer` to `comparison` and update `splits`
+/// display. Unknown comparisons are ignored
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub fn set_comparison(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>, comparison: &str) {
+    let mut timer = match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    if timer.set_current_comparison(comparison).is_err() {
+        warn!("Unknown comparison \"{comparison}\"");
+        return;
+    }
+    info!("Switching comparison to {comparison}");
+    update_splits_display(&timer, splits);
+}
+
+/// Set timing method of `timer` to `timing_method` and update `splits` display
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub fn set_timing_method(
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    timing_method: TimingMethod,
+) {
+    let mut timer = match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    timer.set_current_timing_method(timing_method);
+    info!("Switching timing method to {timing_method:?}");
+    update_splits_display(&timer, splits);
+}
+
+/// Initialize game time of `timer` for current attempt
+///
+/// # Panics
+/// Panics if any errors occur with the timer
+pub fn initialize_game_time(timer: &Arc<RwLock<Timer>>) {
+    match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(mut timer) => timer.initialize_game_time(),
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    }
+}
+
+/// Set game time of `timer` to `game_time`
+///
+/// # Panics
+/// Panics if any errors occur with the timer
+pub fn set_game_time(timer: &Arc<RwLock<Timer>>, game_time: TimeSpan) {
+    match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(mut timer) => timer.set_game_time(game_time),
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    }
+}
+
+/// Set loading times of `timer` (game time is real time minus
+/// `loading_times`)
+///
+/// # Panics
+/// Panics if any errors occur with the timer
+pub fn set_loading_times(timer: &Arc<RwLock<Timer>>, loading_times: TimeSpan) {
+    match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(mut timer) => timer.set_loading_times(loading_times),
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    }
+}
+
+/// Pause game time of `timer` without pausing real time
+///
+/// # Panics
+/// Panics if any errors occur with the timer
+pub fn pause_game_time(timer: &Arc<RwLock<Timer>>) {
+    match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(mut timer) => timer.pause_game_time(),
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    }
+}
+
+/// Resume game time of `timer`
+///
+/// # Panics
+/// Panics if any errors occur with the timer
+pub fn resume_game_time(timer: &Arc<RwLock<Timer>>) {
+    match timer.write().map_err(TimerControlError::TimerWriteLock) {
+        Ok(mut timer) => timer.resume_game_time(),
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::timer_and_splits;
+    use crate::time_format::TimeFormat;
+
+    /// Split `timer` at `seconds` of game time
+    fn split_at(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>, seconds: f64) {
+        set_game_time(timer, TimeSpan::from_seconds(seconds));
+        start_or_split_timer(timer, splits);
+    }
+
+    /// Start `timer` with game time
+    fn start(timer: &Arc<RwLock<Timer>>, splits: &Arc<RwLock<Splits>>) {
+        start_or_split_timer(timer, splits);
+        initialize_game_time(timer);
+        pause_game_time(timer);
+    }
+
+    fn blank() -> String {
+        TimeFormat::default().format_time(TimeSpan::zero())
+    }
+
+    #[test]
+    fn undo_split_blanks_undone_split() {
+        let (timer, splits) = timer_and_splits(&["Intro", "Middle", "End"], TimingMethod::GameTime);
+        start(&timer, &splits);
+        split_at(&timer, &splits, 10.0);
+        split_at(&timer, &splits, 20.0);
+        assert_ne!(splits.read().unwrap().get_time(1), blank());
+
+        undo_split(&timer, &splits);
+
+        assert_eq!(timer.read().unwrap().current_split_index(), Some(1));
+        assert_ne!(splits.read().unwrap().get_time(0), blank());
+        assert_eq!(splits.read().unwrap().get_time(1), blank());
+    }
+
+    #[test]
+    fn skip_split_blanks_skipped_split() {
+        let (timer, splits) = timer_and_splits(&["Intro", "Middle", "End"], TimingMethod::GameTime);
+        start(&timer, &splits);
+        split_at(&timer, &splits, 10.0);
+        skip_split(&timer, &splits);
+        split_at(&timer, &splits, 30.0);
+
+        let timer = timer.read().unwrap();
+        assert_eq!(timer.current_split_index(), Some(3));
+        assert!(timer.run().segment(1).split_time().game_time.is_none());
+        let splits = splits.read().unwrap();
+        assert_ne!(splits.get_time(0), blank());
+        assert_eq!(splits.get_time(1), blank());
+        assert_ne!(splits.get_time(2), blank());
+    }
+
+    #[test]
+    fn toggle_timing_method_shows_times_of_selected_method() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::GameTime);
+        start(&timer, &splits);
+        split_at(&timer, &splits, 3600.0);
+        let game_time = splits.read().unwrap().get_time(0);
+        assert_eq!(
+            game_time,
+            TimeFormat::default().format_time(TimeSpan::from_seconds(3600.0))
+        );
+
+        toggle_timing_method(&timer, &splits);
+
+        assert_eq!(
+            timer.read().unwrap().current_timing_method(),
+            TimingMethod::RealTime
+        );
+        assert_ne!(splits.read().unwrap().get_time(0), game_time);
+        toggle_timing_method(&timer, &splits);
+        assert_eq!(splits.read().unwrap().get_time(0), game_time);
+    }
+
+    #[test]
+    fn toggle_game_time_pause_keeps_real_time_running() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::GameTime);
+        start_or_split_timer(&timer, &splits);
+        initialize_game_time(&timer);
+
+        toggle_game_time_pause(&timer);
+        assert!(timer.read().unwrap().is_game_time_paused());
+        assert_eq!(
+            timer.read().unwrap().current_phase(),
+            livesplit_core::TimerPhase::Running
+        );
+
+        toggle_game_time_pause(&timer);
+        assert!(!timer.read().unwrap().is_game_time_paused());
+    }
+
+    #[test]
+    fn practice_attempts_are_not_recorded() {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::GameTime);
+        start(&timer, &splits);
+        split_at(&timer, &splits, 10.0);
+        split_at(&timer, &splits, 20.0);
+        reset_without_confirmation(&timer, &splits);
+        splits.write().unwrap().set_practice(true);
+
+        start(&timer, &splits);
+        split_at(&timer, &splits, 5.0);
+        reset_without_confirmation(&timer, &splits);
+        // reset without attempt in progress
+        reset_without_confirmation(&timer, &splits);
+
+        let timer = timer.read().unwrap();
+        let splits = splits.read().unwrap();
+        assert_eq!(timer.run().attempt_history().len(), 1);
+        assert_eq!(splits.get_attempt_count(&timer), 1);
+        assert_eq!(splits.get_saved_run(&timer).attempt_count(), 1);
+        let best = timer.run().segment(0).best_segment_time().game_time;
+        assert_eq!(best, Some(TimeSpan::from_seconds(10.0)));
+    }
+
+    /// Returns timer and splits display of a run with a completed attempt
+    /// (10s, 20s) and an attempt in progress with a new best first segment
+    fn attempt_with_best_segment() -> (Arc<RwLock<Timer>>, Arc<RwLock<Splits>>) {
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::GameTime);
+        start(&timer, &splits);
+        split_at(&timer, &splits, 10.0);
+        split_at(&timer, &splits, 20.0);
+        reset_without_confirmation(&timer, &splits);
+        start(&timer, &splits);
+        split_at(&timer, &splits, 5.0);
+        (timer, splits)
+    }
+
+    fn best_first_segment(timer: &Arc<RwLock<Timer>>) -> Option<TimeSpan> {
+        timer
+            .read()
+            .unwrap()
+            .run()
+            .segment(0)
+            .best_segment_time()
+            .game_time
+    }
+
+    #[test]
+    fn split_key_keeps_best_segments_when_asked() {
+        let (timer, splits) = attempt_with_best_segment();
+
+        reset(&timer, &splits);
+        assert!(splits.read().unwrap().is_asking_to_save_golds());
+        assert_ne!(timer.read().unwrap().current_phase(), NotRunning);
+        start_or_split_timer(&timer, &splits);
+
+        assert_eq!(timer.read().unwrap().current_phase(), NotRunning);
+        assert!(!splits.read().unwrap().is_asking_to_save_golds());
+        assert_eq!(
+            best_first_segment(&timer),
+            Some(TimeSpan::from_seconds(5.0))
+        );
+    }
+
+    #[test]
+    fn reset_key_discards_best_segments_when_asked() {
+        let (timer, splits) = attempt_with_best_segment();
+
+        reset(&timer, &splits);
+        reset(&timer, &splits);
+
+        assert_eq!(timer.read().unwrap().current_phase(), NotRunning);
+        assert_eq!(
+            best_first_segment(&timer),
+            Some(TimeSpan::from_seconds(10.0))
+        );
+        // NOTE: like livesplit, the discarded attempt is not in the history
+        assert_eq!(timer.read().unwrap().run().attempt_history().len(), 1);
+    }
+
+    #[test]
+    fn reset_key_is_pressed_twice_with_double_press() {
+        let (timer, splits) = attempt_with_best_segment();
+        splits
+            .write()
+            .unwrap()
+            .set_reset_policy(crate::ResetPolicy {
+                double_press: true,
+                ask_to_save_golds: false,
+                ..crate::ResetPolicy::default()
+            });
+
+        reset(&timer, &splits);
+        assert_ne!(timer.read().unwrap().current_phase(), NotRunning);
+        reset(&timer, &splits);
+
+        assert_eq!(timer.read().unwrap().current_phase(), NotRunning);
+        assert_eq!(
+            best_first_segment(&timer),
+            Some(TimeSpan::from_seconds(5.0))
+        );
+    }
 }
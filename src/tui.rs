@@ -0,0 +1,250 @@
+//! Display the timer in the terminal, for machines without a graphical session
+//!
+//! Hotkeys work the same as with the graphical window. The terminal only
+//! reads `q` (followed by enter) to save attempts and quit
+use crate::persistence::{save_run_to_file, SpeedrunSettings};
+use crate::ui::{
+    delta_palette, keybinding_legend, summary_rows, timing_method_name, truncate_comparison_name,
+    DeltaClass, Splits, UIError,
+};
+use crate::Keybinding;
+use livesplit_core::Timer;
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// Delay between two repaints of the terminal
+const REFRESH_DELAY: Duration = Duration::from_millis(50);
+
+/// Paint `timer` and `splits` in the terminal until user quits, then save
+/// attempts of the run
+///
+/// # Errors
+/// Returns an error if the terminal cannot be written to
+///
+/// # Panics
+/// Panics if any errors occur with the timer or the splits display
+pub fn run_tui(
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    keybinding: Keybinding,
+    settings: &SpeedrunSettings,
+) -> Result<(), std::io::Error> {
+    let mut legend = keybinding_legend(keybinding);
+    legend.push(String::new());
+    legend.push("Quit (saves attempts): q + Enter".to_string());
+    let palette = delta_palette(&settings.get_delta_colors());
+
+    let (quit_sender, quit_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            match line {
+                Ok(line) if line.trim() == "q" => {
+                    if quit_sender.send(()).is_err() {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("{e}");
+                    return;
+                }
+            }
+        }
+        // NOTE: without stdin (closed or redirected), the timer keeps running
+        info!("stdin closed, quitting with q is disabled");
+    });
+
+    let mut stdout = std::io::stdout();
+    // hide cursor and clear screen
+    write!(stdout, "\x1b[?25l\x1b[2J")?;
+    loop {
+        if quit_receiver.try_recv().is_ok() {
+            break;
+        }
+        let lines = render(timer, splits, &legend, &palette, settings);
+        // move cursor to top left then overwrite each line
+        write!(stdout, "\x1b[H")?;
+        for line in lines {
+            writeln!(stdout, "{line}\x1b[K")?;
+        }
+        write!(stdout, "\x1b[J")?;
+        stdout.flush()?;
+        thread::sleep(REFRESH_DELAY);
+    }
+    // show cursor again
+    write!(stdout, "\x1b[?25h")?;
+    stdout.flush()?;
+
+    let timer = match timer.read().map_err(UIError::TimerReadLock) {
+        Ok(timer) => timer,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let splits = match splits.read().map_err(UIError::SplitsReadLock) {
+        Ok(splits) => splits,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    if splits.is_practicing() {
+        info!("Practice mode: run not saved");
+    } else if let Err(e) = save_run_to_file(&splits.get_saved_run(&timer), settings) {
+        error!("{e}");
+    } else {
+        info!("Saved run");
+    }
+    Ok(())
+}
+
+/// Returns lines of the splits panel, same as the graphical default panel
+fn render(
+    timer: &Arc<RwLock<Timer>>,
+    splits: &Arc<RwLock<Splits>>,
+    legend: &[String],
+    palette: &HashMap<DeltaClass, [u8; 3]>,
+    settings: &SpeedrunSettings,
+) -> Vec<String> {
+    // NOTE: timer is locked before splits, like timer controls do
+    let timer = match timer.read().map_err(UIError::TimerReadLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let splits = match splits.read().map_err(UIError::SplitsReadLock) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("{e}");
+            panic!("{e}") // cannot recover
+        }
+    };
+    let timing_method = timer.current_timing_method();
+    let time_format = settings.get_time_format();
+    let current_time = timer.snapshot().current_time()[timing_method].unwrap_or_default();
+    let current_time = time_format.format_time(current_time);
+    let padding = splits.get_name_padding();
+    let run = timer.run();
+    let comparison_name = truncate_comparison_name(timer.current_comparison());
+
+    let mut lines = vec![format!("\x1b[1m{}\x1b[0m", run.game_name())];
+    for notice in splits.get_notices() {
+        lines.push(format!("\x1b[1;33m{notice}\x1b[0m"));
+    }
+    lines.extend([
+        format!("Category: {}", run.category_name()),
+        format!("Attempts: {}", splits.get_attempt_count(&timer)),
+        format!("Timing  : {}", timing_method_name(timing_method)),
+    ]);
+    if let Some(status) = splits.get_auto_splitter_status() {
+        lines.push(format!("Auto    : {status}"));
+    }
+    lines.extend([format!(
+        "{:<padding$}: {} {comparison_name:<13} Time difference{}",
+        "Splits",
+        splits.get_time_header(),
+        splits.get_best_segment_header()
+    )]);
+    for i in 0..splits.len() {
+        let current_split = timer.current_split_index() == Some(i);
+        // highlight current split and dim splits outside of segment practice
+        let style = if current_split {
+            "\x1b[7m"
+        } else if splits.is_practiced(i) {
+            ""
+        } else {
+            "\x1b[2m"
+        };
+        let (time, comparison, time_difference, delta_class) = splits.get_row(i);
+        let best_segment_column = match splits.get_best_segment_column(i) {
+            Some((best_segment_difference, delta_class)) => {
+                format!(
+                    " {}",
+                    colored(best_segment_difference, delta_class, palette)
+                )
+            }
+            None => String::new(),
+        };
+        lines.push(format!(
+            "{style}{:<padding$}: {time} {comparison} {}{best_segment_column}{}",
+            splits.get_split_name(i),
+            colored(time_difference, delta_class, palette),
+            if style.is_empty() { "" } else { "\x1b[0m" },
+        ));
+    }
+    lines.push(format!("{:<padding$}: {current_time}", "Time"));
+    for (name, value) in summary_rows(&timer, settings.get_summary_rows(), time_format) {
+        lines.push(format!("{name:<padding$}: {value}"));
+    }
+    lines.push(String::new());
+    lines.extend(legend.iter().cloned());
+    lines
+}
+
+/// Returns `text` colored with color of `delta_class` in `palette`, if any
+fn colored(
+    text: String,
+    delta_class: DeltaClass,
+    palette: &HashMap<DeltaClass, [u8; 3]>,
+) -> String {
+    match palette.get(&delta_class) {
+        Some([r, g, b]) => format!("\x1b[38;2;{r};{g};{b}m{text}\x1b[39m"),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::timer_and_splits;
+    use crate::DeltaColors;
+    use livesplit_core::TimingMethod;
+
+    #[test]
+    fn unclassified_text_is_not_colored() {
+        let palette = delta_palette(&DeltaColors::default());
+        assert_eq!(colored("-".to_string(), DeltaClass::None, &palette), "-");
+        assert_eq!(
+            colored("-1.00".to_string(), DeltaClass::Gold, &palette),
+            "\x1b[38;2;216;175;31m-1.00\x1b[39m"
+        );
+    }
+
+    #[test]
+    fn panel_lists_splits() {
+        let settings: SpeedrunSettings = toml::from_str(
+            r#"
+            split_names = ["Intro", "End"]
+            game_name = "Game"
+            category_name = "Any%"
+
+            [keybindings]
+            split_key = "Numpad1"
+            reset_key = "Numpad3"
+            pause_key = "Numpad5"
+            unpause_key = "Numpad7"
+            comparison_key = "Numpad9"
+            "#,
+        )
+        .unwrap();
+        let (timer, splits) = timer_and_splits(&["Intro", "End"], TimingMethod::RealTime);
+        let mut run = timer.read().unwrap().run().clone();
+        run.set_game_name(settings.get_game_name());
+        timer.write().unwrap().set_run(run).unwrap();
+
+        let lines = render(&timer, &splits, &[], &HashMap::new(), &settings);
+
+        assert_eq!(lines[0], "\x1b[1mGame\x1b[0m");
+        assert!(lines.contains(&"Attempts: 0".to_string()));
+        assert!(lines.iter().any(|line| line.starts_with("Intro: ")));
+        assert!(lines.iter().any(|line| line.starts_with("End  : ")));
+    }
+}
@@ -1,14 +1,44 @@
 //! Manage all UI elements
-use crate::persistence::{save_run_to_file, SpeedrunSettings};
-use crate::Keybinding;
-use eframe::egui;
-use eframe::Storage;
-use egui_extras::RetainedImage;
+use crate::segment_practice::SegmentPractice;
+use crate::time_format::{format_delta, format_time, TimeFormat};
+#[cfg(feature = "gui")]
+use crate::{autosave::run_version, stats::RunStatistics};
+use crate::{DeltaColors, Keybinding, ResetPolicy, SummaryRows};
+use livesplit_core::analysis::{self, current_pace, possible_time_save, sum_of_segments};
+use livesplit_core::comparison::best_segments;
+use livesplit_core::hotkey::KeyCode;
+use livesplit_core::settings::SemanticColor;
+use livesplit_core::Run;
 use livesplit_core::TimeSpan;
 use livesplit_core::Timer;
-use log::{error, info, warn};
+use livesplit_core::TimingMethod;
+use log::warn;
+use std::collections::HashMap;
 use std::fmt;
-use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+use std::sync::{PoisonError, RwLockReadGuard};
+use std::time::{Duration, Instant};
+#[cfg(feature = "gui")]
+use {
+    crate::persistence::{save_run_to_file, SpeedrunSettings},
+    eframe::{egui, Storage},
+    egui_extras::RetainedImage,
+    livesplit_core::component::{detailed_timer, key_value, splits, text, timer, title},
+    livesplit_core::layout::{ComponentState, GeneralSettings, LayoutState},
+    livesplit_core::settings::{Color, Gradient},
+    livesplit_core::Layout,
+    log::{error, info},
+    std::sync::{Arc, RwLock},
+};
+
+/// Shown in the header in practice mode
+const PRACTICE_NOTICE: &str = "Practice mode: attempts are not recorded";
+
+/// Shown in the header after the first press of the reset key
+const RESET_CONFIRMATION_NOTICE: &str = "Press reset key again to reset";
+
+/// Shown in the header when improved best segments of an unfinished attempt
+/// might be discarded
+const SAVE_GOLDS_PROMPT: &str = "Best segments improved. Keep them? Split key: yes, reset key: no";
 
 /// Errors while displaying the timer
 #[derive(Debug)]
@@ -43,25 +73,81 @@ impl<'a> From<PoisonError<RwLockReadGuard<'a, livesplit_core::Timer>>> for UIErr
     }
 }
 
+/// Semantic classification of the time difference of a split
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DeltaClass {
+    /// No time difference to classify
+    #[default]
+    None,
+    /// Best segment ever
+    Gold,
+    /// Ahead of comparison and gained time on last segment
+    AheadGaining,
+    /// Ahead of comparison but lost time on last segment
+    AheadLosing,
+    /// Behind comparison but gained time on last segment
+    BehindGaining,
+    /// Behind comparison and lost time on last segment
+    BehindLosing,
+}
+
+impl From<SemanticColor> for DeltaClass {
+    fn from(color: SemanticColor) -> Self {
+        match color {
+            SemanticColor::BestSegment => DeltaClass::Gold,
+            SemanticColor::AheadGainingTime => DeltaClass::AheadGaining,
+            SemanticColor::AheadLosingTime => DeltaClass::AheadLosing,
+            SemanticColor::BehindGainingTime => DeltaClass::BehindGaining,
+            SemanticColor::BehindLosingTime => DeltaClass::BehindLosing,
+            _ => DeltaClass::None,
+        }
+    }
+}
+
 #[derive(Default)]
 struct Split {
     name: String,
     time: TimeSpan,
     comparison: TimeSpan,
     time_difference: TimeSpan,
+    delta_class: DeltaClass,
+    segment_time: Option<TimeSpan>,
+    comparison_segment_time: Option<TimeSpan>,
+    segment_difference: Option<TimeSpan>,
+    best_segment_difference: Option<TimeSpan>,
+    segment_delta_class: DeltaClass,
 }
 
 #[derive(Default)]
 /// Time splits of a speedrun
+#[allow(clippy::struct_field_names)]
 pub struct Splits {
     splits: Vec<Split>,
     name_padding: usize,
+    /// loading status of the auto splitter, if any
+    auto_splitter_status: Option<String>,
+    /// display segment times instead of cumulative split times
+    show_segment_times: bool,
+    time_format: TimeFormat,
+    /// attempts are not recorded
+    practice: bool,
+    segment_practice: Option<SegmentPractice>,
+    reset_policy: ResetPolicy,
+    /// first press of the reset key waiting for confirmation
+    reset_pressed_at: Option<Instant>,
+    /// user is asked whether improved best segments are kept
+    asking_to_save_golds: bool,
+    /// offset of the run file, replaced while a recovered attempt is resumed
+    run_offset: Option<TimeSpan>,
+    /// practice attempts counted by the timer, not part of the attempt count
+    practice_attempts: u32,
 }
 
 impl Splits {
-    /// create [Splits](Splits) items from `split_names`
+    /// create [Splits](Splits) items from `split_names` displayed with
+    /// `time_format`
     #[must_use]
-    pub fn new(split_names: Vec<String>) -> Splits {
+    pub fn new(split_names: Vec<String>, time_format: TimeFormat) -> Splits {
         let mut splits: Vec<Split> = Vec::new();
         for name in split_names.clone() {
             splits.push(Split {
@@ -69,6 +155,12 @@ impl Splits {
                 time: TimeSpan::default(),
                 comparison: TimeSpan::default(),
                 time_difference: TimeSpan::default(),
+                delta_class: DeltaClass::None,
+                segment_time: None,
+                comparison_segment_time: None,
+                segment_difference: None,
+                best_segment_difference: None,
+                segment_delta_class: DeltaClass::None,
             });
         }
 
@@ -80,10 +172,21 @@ impl Splits {
                 .map(|name| name.len())
                 .max()
                 .unwrap_or(0),
+            auto_splitter_status: None,
+            show_segment_times: false,
+            time_format,
+            practice: false,
+            segment_practice: None,
+            reset_policy: ResetPolicy::default(),
+            reset_pressed_at: None,
+            asking_to_save_golds: false,
+            run_offset: None,
+            practice_attempts: 0,
         }
     }
 
-    /// Updates displayed split `i`
+    /// Updates displayed split `i`. A zero `time` blanks the time difference
+    /// (split not reached, undone or skipped)
     pub fn update_split(&mut self, i: usize, time: TimeSpan, comparison: TimeSpan) {
         self.splits[i].time = time;
         // Comparison time gets filled at application start. When the timer
@@ -93,48 +196,339 @@ impl Splits {
         }
         if time > TimeSpan::zero() {
             self.splits[i].time_difference = time - comparison;
+        } else {
+            self.splits[i].time_difference = TimeSpan::zero();
+        }
+    }
+
+    /// Classify time difference of every split and compute segment times
+    /// with the current comparison and timing method of `timer`
+    pub fn update_analysis(&mut self, timer: &Timer) {
+        let comparison = timer.current_comparison();
+        let timing_method = timer.current_timing_method();
+        let segment_practice = self.segment_practice.is_some();
+        // time difference with comparison accumulated over segments
+        let mut cumulative_difference = TimeSpan::zero();
+        for (i, split) in self.splits.iter_mut().enumerate() {
+            let time_difference = (split.time > TimeSpan::zero()
+                && split.comparison > TimeSpan::zero())
+            .then_some(split.time_difference);
+            split.delta_class = analysis::split_color(
+                timer,
+                time_difference,
+                i,
+                true,
+                true,
+                comparison,
+                timing_method,
+            )
+            .into();
+
+            split.segment_time = analysis::previous_segment_time(timer, i, timing_method);
+            split.comparison_segment_time =
+                analysis::comparison_single_segment_time(timer.run(), i, comparison, timing_method);
+            // NOTE: livesplit adds skipped segments to the segment delta and
+            //       splits before the practiced ones are skipped
+            split.segment_difference = if segment_practice {
+                split
+                    .segment_time
+                    .zip(split.comparison_segment_time)
+                    .map(|(time, comparison)| time - comparison)
+            } else {
+                analysis::previous_segment_delta(timer, i, comparison, timing_method)
+            };
+            let best = timer.run().segment(i).best_segment_time()[timing_method];
+            split.best_segment_difference =
+                split.segment_time.zip(best).map(|(time, best)| time - best);
+            let best_segment = if segment_practice {
+                split
+                    .segment_time
+                    .is_some_and(|time| best.is_none_or(|best| time < best))
+            } else {
+                analysis::check_best_segment(timer, i, timing_method)
+            };
+            if let Some(d) = split.segment_difference {
+                cumulative_difference += d;
+            }
+            split.segment_delta_class = match split.segment_time.and(split.segment_difference) {
+                None => DeltaClass::None,
+                Some(_) if best_segment => DeltaClass::Gold,
+                Some(d) => segment_delta_class(
+                    cumulative_difference < TimeSpan::zero(),
+                    d < TimeSpan::zero(),
+                ),
+            };
         }
     }
 
+    /// Enable or disable practice mode
+    pub fn set_practice(&mut self, practice: bool) {
+        self.practice = practice;
+    }
+
+    /// Returns true in practice mode, where attempts are not recorded
+    #[must_use]
+    pub fn is_practicing(&self) -> bool {
+        self.practice
+    }
+
+    /// Practice only segments of `segment_practice`
+    pub fn set_segment_practice(&mut self, segment_practice: Option<SegmentPractice>) {
+        self.segment_practice = segment_practice;
+    }
+
+    /// Returns practiced segments if only some segments are practiced
+    #[must_use]
+    pub fn get_segment_practice(&self) -> Option<&SegmentPractice> {
+        self.segment_practice.as_ref()
+    }
+
+    /// Returns true if split `i` is practiced (all splits are outside of
+    /// segment practice)
+    pub(crate) fn is_practiced(&self, i: usize) -> bool {
+        self.segment_practice.as_ref().is_none_or(|p| p.contains(i))
+    }
+
+    /// Restore `run_offset` of the run file once the resumed attempt is reset
+    pub fn set_run_offset(&mut self, run_offset: TimeSpan) {
+        self.run_offset = Some(run_offset);
+    }
+
+    /// Returns offset of the run file to restore, if a recovered attempt was
+    /// resumed
+    pub(crate) fn take_run_offset(&mut self) -> Option<TimeSpan> {
+        self.run_offset.take()
+    }
+
+    /// Returns true if the attempt in progress is a resumed interrupted attempt
+    #[must_use]
+    pub fn is_resumed_attempt(&self) -> bool {
+        self.run_offset.is_some()
+    }
+
+    /// Remove practice attempt counted by the timer from the attempt count
+    pub(crate) fn uncount_practice_attempt(&mut self) {
+        self.practice_attempts += 1;
+    }
+
+    /// Returns attempt count of run of `timer`, without practice attempts
+    #[must_use]
+    pub fn get_attempt_count(&self, timer: &Timer) -> u32 {
+        timer
+            .run()
+            .attempt_count()
+            .saturating_sub(self.practice_attempts)
+    }
+
+    /// Returns run of `timer` as it should be saved, with the offset of the
+    /// run file and without practice attempts
+    pub(crate) fn get_saved_run(&self, timer: &Timer) -> Run {
+        let mut run = timer.run().clone();
+        if let Some(run_offset) = self.run_offset {
+            run.set_offset(run_offset);
+        }
+        run.set_attempt_count(self.get_attempt_count(timer));
+        run
+    }
+
+    /// Ask confirmations of `reset_policy` when resetting
+    pub fn set_reset_policy(&mut self, reset_policy: ResetPolicy) {
+        self.reset_policy = reset_policy;
+    }
+
+    /// Returns confirmations asked when resetting
+    pub(crate) fn get_reset_policy(&self) -> ResetPolicy {
+        self.reset_policy
+    }
+
+    /// Returns true if the reset key was already pressed within the double
+    /// press window. Otherwise, waits for a second press
+    pub(crate) fn confirm_reset(&mut self) -> bool {
+        if self.is_reset_pending() {
+            self.reset_pressed_at = None;
+            return true;
+        }
+        self.reset_pressed_at = Some(Instant::now());
+        false
+    }
+
+    /// Returns true if the reset key was pressed once within the double press
+    /// window
+    fn is_reset_pending(&self) -> bool {
+        let window = Duration::from_millis(self.reset_policy.double_press_window_ms);
+        self.reset_pressed_at
+            .is_some_and(|pressed_at| pressed_at.elapsed() <= window)
+    }
+
+    /// Ask user whether improved best segments are kept
+    pub(crate) fn ask_to_save_golds(&mut self) {
+        self.asking_to_save_golds = true;
+    }
+
+    /// Returns true while user is asked whether improved best segments are
+    /// kept
+    pub(crate) fn is_asking_to_save_golds(&self) -> bool {
+        self.asking_to_save_golds
+    }
+
+    /// Forget pending reset confirmations
+    pub(crate) fn clear_reset_confirmations(&mut self) {
+        self.reset_pressed_at = None;
+        self.asking_to_save_golds = false;
+    }
+
+    /// Returns notices shown in the header (practice mode, reset
+    /// confirmations)
+    pub(crate) fn get_notices(&self) -> Vec<&'static str> {
+        let mut notices = vec![];
+        if self.practice {
+            notices.push(PRACTICE_NOTICE);
+        }
+        if self.asking_to_save_golds {
+            notices.push(SAVE_GOLDS_PROMPT);
+        } else if self.is_reset_pending() {
+            notices.push(RESET_CONFIRMATION_NOTICE);
+        }
+        notices
+    }
+
+    /// Switch between cumulative split times and segment times
+    pub fn toggle_segment_times(&mut self) {
+        self.show_segment_times = !self.show_segment_times;
+    }
+
     /// Reset display split
     pub fn clear_time_differences(&mut self) {
         for i in 0..self.splits.len() {
+            self.splits[i].delta_class = DeltaClass::None;
             self.splits[i].time_difference = TimeSpan::zero();
+            self.splits[i].segment_time = None;
+            self.splits[i].segment_difference = None;
+            self.splits[i].best_segment_difference = None;
+            self.splits[i].segment_delta_class = DeltaClass::None;
         }
     }
 
     /// Get name of split `i`
-    fn get_split_name(&self, i: usize) -> String {
+    pub(crate) fn get_split_name(&self, i: usize) -> String {
         self.splits[i].name.clone()
     }
 
     /// Get time of split `i`
-    fn get_time(&self, i: usize) -> Result<String, UIError> {
-        format_timespan(self.splits[i].time)
+    pub(crate) fn get_time(&self, i: usize) -> String {
+        self.time_format.format_time(self.splits[i].time)
     }
 
     /// Get formatted comparison of split `i`
-    fn get_comparison(&self, i: usize) -> Result<String, UIError> {
-        format_timespan(self.splits[i].comparison)
+    pub(crate) fn get_comparison(&self, i: usize) -> String {
+        self.time_format.format_time(self.splits[i].comparison)
     }
 
     /// Get formatted time difference with comparison of split `i`. '-'
     /// indicates a timesave
-    fn get_time_difference(&self, i: usize) -> String {
-        let time_difference = self.splits[i].time_difference;
-        let sign = if time_difference.to_duration().is_negative() {
-            '-'
-        } else if time_difference.to_duration().is_positive() {
-            '+'
+    pub(crate) fn get_time_difference(&self, i: usize) -> String {
+        self.time_format
+            .format_delta(self.splits[i].time_difference)
+    }
+
+    /// Returns true if segment times are displayed instead of split times
+    pub(crate) fn is_showing_segment_times(&self) -> bool {
+        self.show_segment_times
+    }
+
+    /// Get formatted segment time of split `i`
+    pub(crate) fn get_segment_time(&self, i: usize) -> String {
+        self.time_format
+            .format_time(self.splits[i].segment_time.unwrap_or_default())
+    }
+
+    /// Get formatted segment time of comparison of split `i`
+    pub(crate) fn get_comparison_segment_time(&self, i: usize) -> String {
+        self.time_format
+            .format_time(self.splits[i].comparison_segment_time.unwrap_or_default())
+    }
+
+    /// Get formatted segment time difference with comparison of split `i`.
+    /// '-' indicates a timesave
+    pub(crate) fn get_segment_difference(&self, i: usize) -> String {
+        self.time_format
+            .format_delta(self.splits[i].segment_difference.unwrap_or_default())
+    }
+
+    /// Get formatted segment time difference with best segment of split `i`.
+    /// '-' indicates a new best segment
+    pub(crate) fn get_best_segment_difference(&self, i: usize) -> String {
+        self.time_format
+            .format_delta(self.splits[i].best_segment_difference.unwrap_or_default())
+    }
+
+    /// Get classification of segment time difference of split `i`
+    pub(crate) fn get_segment_delta_class(&self, i: usize) -> DeltaClass {
+        self.splits[i].segment_delta_class
+    }
+
+    /// Get classification of time difference of split `i`
+    pub(crate) fn get_delta_class(&self, i: usize) -> DeltaClass {
+        self.splits[i].delta_class
+    }
+
+    /// Get time, comparison, time difference and its classification of split
+    /// `i`, as segment times when they are displayed or in segment practice
+    pub(crate) fn get_row(&self, i: usize) -> (String, String, String, DeltaClass) {
+        if self.is_showing_segment_times() || self.segment_practice.is_some() {
+            (
+                self.get_segment_time(i),
+                self.get_comparison_segment_time(i),
+                self.get_segment_difference(i),
+                self.get_segment_delta_class(i),
+            )
         } else {
-            ' '
-        };
-        let time_difference = format_timesave(time_difference);
-        format!("{sign}{time_difference}")
+            (
+                self.get_time(i),
+                self.get_comparison(i),
+                self.get_time_difference(i),
+                self.get_delta_class(i),
+            )
+        }
+    }
+
+    /// Get segment time difference with best segment of split `i`, gold for
+    /// a new best segment, if segment times are displayed
+    pub(crate) fn get_best_segment_column(&self, i: usize) -> Option<(String, DeltaClass)> {
+        self.is_showing_segment_times().then(|| {
+            let delta_class = match self.get_segment_delta_class(i) {
+                DeltaClass::Gold => DeltaClass::Gold,
+                _ => DeltaClass::None,
+            };
+            (self.get_best_segment_difference(i), delta_class)
+        })
+    }
+
+    /// Returns header of the time column
+    pub(crate) fn get_time_header(&self) -> &'static str {
+        if self.is_showing_segment_times() || self.segment_practice.is_some() {
+            "Segment time"
+        } else {
+            "Current time"
+        }
+    }
+
+    /// Returns header of the best segment column, if displayed
+    pub(crate) fn get_best_segment_header(&self) -> &'static str {
+        if self.is_showing_segment_times() {
+            " Best segment delta"
+        } else {
+            ""
+        }
+    }
+
+    /// Returns padding of split names (length of longest name)
+    pub(crate) fn get_name_padding(&self) -> usize {
+        self.name_padding
     }
 
     /// Returns the number of splits
-    fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         self.splits.len()
     }
 
@@ -145,24 +539,67 @@ impl Splits {
             self.splits[i].time_difference = self.splits[i].time - self.splits[i].comparison;
         }
     }
+
+    /// Show loading `status` of the auto splitter
+    pub fn set_auto_splitter_status(&mut self, status: String) {
+        self.auto_splitter_status = Some(status);
+    }
+
+    /// Returns loading status of the auto splitter, if the speedrun uses one
+    pub(crate) fn get_auto_splitter_status(&self) -> Option<&str> {
+        self.auto_splitter_status.as_deref()
+    }
+}
+
+/// Returns classification of a segment time difference, `ahead` of the
+/// comparison so far and `gaining` time on the segment
+fn segment_delta_class(ahead: bool, gaining: bool) -> DeltaClass {
+    match (ahead, gaining) {
+        (true, true) => DeltaClass::AheadGaining,
+        (true, false) => DeltaClass::AheadLosing,
+        (false, true) => DeltaClass::BehindGaining,
+        (false, false) => DeltaClass::BehindLosing,
+    }
+}
+
+/// Show monospace `text` colored with color of `delta_class` in `palette`, if
+/// any
+#[cfg(feature = "gui")]
+fn colored_label(
+    ui: &mut egui::Ui,
+    text: String,
+    delta_class: DeltaClass,
+    palette: &HashMap<DeltaClass, [u8; 3]>,
+) {
+    let text = egui::RichText::new(text).monospace();
+    match palette.get(&delta_class) {
+        Some([r, g, b]) => ui.label(text.color(egui::Color32::from_rgb(*r, *g, *b))),
+        None => ui.label(text),
+    };
 }
 
 /// Speedrun and all its associated settings
+#[cfg(feature = "gui")]
 pub struct SpeedrunDisplay {
     name: String,
     timer: Arc<RwLock<Timer>>,
     splits: Arc<RwLock<Splits>>,
-    split_key: String,
-    reset_key: String,
-    pause_key: String,
-    unpause_key: String,
-    comparison_key: String,
+    legend: Vec<String>,
+    delta_palette: HashMap<DeltaClass, [u8; 3]>,
     settings: SpeedrunSettings,
-    icons: Vec<RetainedImage>,
+    /// icons of splits, indexed by segment
+    icons: HashMap<usize, RetainedImage>,
+    layout: Option<Layout>,
+    layout_state: LayoutState,
+    /// icons of splits component, indexed by segment
+    layout_icons: HashMap<usize, RetainedImage>,
+    statistics: Option<CachedStatistics>,
 }
 
+#[cfg(feature = "gui")]
 impl SpeedrunDisplay {
-    /// Create speedrun ui for pmls application
+    /// Create speedrun ui for pmls application. When provided, `layout` is
+    /// displayed instead of the default splits panel
     #[must_use]
     pub fn new(
         name: String,
@@ -170,18 +607,20 @@ impl SpeedrunDisplay {
         splits: Arc<RwLock<Splits>>,
         keybinding: Keybinding,
         settings: SpeedrunSettings,
+        layout: Option<Layout>,
     ) -> Self {
         Self {
             name,
             timer,
             splits,
-            split_key: format!("{:?}", keybinding.split_key),
-            reset_key: format!("{:?}", keybinding.reset_key),
-            pause_key: format!("{:?}", keybinding.pause_key),
-            unpause_key: format!("{:?}", keybinding.unpause_key),
-            comparison_key: format!("{:?}", keybinding.comparison_key),
+            legend: keybinding_legend(keybinding),
+            delta_palette: delta_palette(&settings.get_delta_colors()),
             settings,
-            icons: vec![],
+            icons: HashMap::new(),
+            layout,
+            layout_state: LayoutState::default(),
+            layout_icons: HashMap::new(),
+            statistics: None,
         }
     }
 
@@ -201,100 +640,161 @@ impl SpeedrunDisplay {
     pub fn init(&mut self) -> Result<(), UIError> {
         info!("preloading speedrun icons...");
         let timer = self.timer.read()?;
-        for segment in timer.run().segments() {
-            let img_data = segment.icon().data();
-            if !img_data.is_empty() {
-                let image = RetainedImage::from_image_bytes(segment.name(), img_data)?;
-                self.icons.push(image);
-            }
-        }
+        self.icons = load_icons(timer.run())?;
 
         Ok(())
     }
+
+    /// Paint speedrun with its layout. Keybinding `legend` is collapsed
+    fn show_layout(&mut self, ctx: &egui::Context, legend: Vec<String>) {
+        let Some(layout) = self.layout.as_mut() else {
+            return;
+        };
+        let timer_readonly = match self.timer.read().map_err(UIError::TimerReadLock) {
+            Ok(m) => m,
+            Err(e) => {
+                error!("{e}");
+                panic!("{e}") // cannot recover
+            }
+        };
+        let notices = match self.splits.read().map_err(UIError::SplitsReadLock) {
+            Ok(splits) => splits.get_notices(),
+            Err(e) => {
+                error!("{e}");
+                panic!("{e}") // cannot recover
+            }
+        };
+        layout.update_state(&mut self.layout_state, &timer_readonly.snapshot());
+        update_layout_icons(&mut self.layout_icons, &self.layout_state);
+        let general_settings = layout.general_settings();
+        let background = egui::Frame::none()
+            .fill(gradient_color(self.layout_state.background))
+            .inner_margin(8.0);
+        egui::CentralPanel::default()
+            .frame(background)
+            .show(ctx, |ui| {
+                for notice in notices {
+                    ui.colored_label(egui::Color32::YELLOW, notice);
+                }
+                show_layout_state(ui, &self.layout_state, general_settings, &self.layout_icons);
+                ui.collapsing("Keybindings", |ui| {
+                    for line in legend {
+                        ui.monospace(line);
+                    }
+                });
+                show_statistics(
+                    ui,
+                    &timer_readonly,
+                    self.settings.get_time_format(),
+                    &mut self.statistics,
+                );
+            });
+
+        // continously repaint even if out of focus
+        ctx.request_repaint();
+    }
 }
 
+#[cfg(feature = "gui")]
 impl eframe::App for SpeedrunDisplay {
     // NOTE: obtaining a write lock inside the update function does not work.
     //       The workaround is to bind a key to a callback function.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let splits = match self.splits.read().map_err(UIError::SplitsReadLock) {
+        let legend = self.legend.clone();
+        if self.layout.is_some() {
+            self.show_layout(ctx, legend);
+            return;
+        }
+
+        // NOTE: timer is locked before splits, like timer controls do
+        let timer_readonly = match self.timer.read().map_err(UIError::TimerReadLock) {
             Ok(m) => m,
             Err(e) => {
                 error!("{e}");
                 panic!("{e}") // cannot recover
             }
         };
-        let timer_readonly = match self.timer.read().map_err(UIError::TimerReadLock) {
+        let splits = match self.splits.read().map_err(UIError::SplitsReadLock) {
             Ok(m) => m,
             Err(e) => {
                 error!("{e}");
                 panic!("{e}") // cannot recover
             }
         };
-        let current_time = match timer_readonly.snapshot().current_time().real_time {
+        let timing_method = timer_readonly.current_timing_method();
+        let current_time = match timer_readonly.snapshot().current_time()[timing_method] {
             Some(ts) => ts,
             None => {
                 warn!("Current time could not be parsed");
                 TimeSpan::default()
             }
         };
-        let current_time = format_timespan(current_time).unwrap();
+        let time_format = self.settings.get_time_format();
+        let current_time = time_format.format_time(current_time);
         let padding = splits.name_padding;
         let run = timer_readonly.run();
         let category_name = run.category_name();
-        let attempts_count = run.attempt_count();
-        let comparison_name = timer_readonly.current_comparison();
-        // truncate if too long
-        let comparison_name = {
-            if comparison_name.len() >= 13 {
-                let (l, _) = comparison_name.split_at(10);
-                let comparison_name = format!("{l}...");
-                comparison_name
-            } else {
-                comparison_name.to_string()
-            }
-        };
+        let attempts_count = splits.get_attempt_count(&timer_readonly);
+        let comparison_name = truncate_comparison_name(timer_readonly.current_comparison());
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading(run.game_name());
+            for notice in splits.get_notices() {
+                ui.colored_label(egui::Color32::YELLOW, notice);
+            }
             ui.monospace(format!("Category: {}", category_name));
             ui.monospace(format!("Attempts: {attempts_count}"));
+            ui.monospace(format!("Timing  : {}", timing_method_name(timing_method)));
+            if let Some(status) = splits.get_auto_splitter_status() {
+                ui.monospace(format!("Auto    : {status}"));
+            }
 
+            let image_padding = icon_padding(&self.icons);
             ui.horizontal(|ui| {
-                let image_padding = run_has_icon(run);
                 ui.monospace(format!(
-                    "{}{:<padding$}: Current time {:<13} Time difference",
-                    image_padding, "Splits", comparison_name
+                    "{}{:<padding$}: {} {:<13} Time difference{}",
+                    image_padding,
+                    "Splits",
+                    splits.get_time_header(),
+                    comparison_name,
+                    splits.get_best_segment_header()
                 ));
             });
             for i in 0..splits.len() {
                 ui.horizontal(|ui| {
-                    // example: https://github.com/emilk/egui/blob/0.17.0/eframe/examples/image.rs
-                    if let Some(img) = self.icons.get(i) {
-                        // 27 pixels is ~= 5 whitespaces
-                        let dimensions = egui::Vec2::new(27f32,27f32);
-                        //ui.image(image.texture_id(ctx), image.size_vec2());
-                        ui.image(img.texture_id(ctx), dimensions);
-                    }
+                    // grey out splits outside of segment practice
+                    ui.set_enabled(splits.is_practiced(i));
+                    show_icon(ui, ctx, &self.icons, i);
                     ui.monospace(format!("{:<padding$}:", splits.get_split_name(i)));
-                    ui.monospace(splits.get_time(i).unwrap());
-                    ui.monospace(splits.get_comparison(i).unwrap());
-                    ui.monospace(splits.get_time_difference(i));
+                    let (time, comparison, time_difference, delta_class) = splits.get_row(i);
+                    ui.monospace(time);
+                    ui.monospace(comparison);
+                    colored_label(ui, time_difference, delta_class, &self.delta_palette);
+                    if let Some((best_segment_difference, delta_class)) =
+                        splits.get_best_segment_column(i)
+                    {
+                        colored_label(ui, best_segment_difference, delta_class, &self.delta_palette);
+                    }
                 });
             }
             ui.horizontal(|ui| {
-                let image_padding = run_has_icon(run);
                 ui.monospace(format!("{}{:<padding$}:", image_padding, "Time"));
                 ui.monospace(current_time);
             });
+            for (name, value) in summary_rows(
+                &timer_readonly,
+                self.settings.get_summary_rows(),
+                time_format,
+            ) {
+                ui.monospace(format!("{image_padding}{name:<padding$}: {value}"));
+            }
             ui.monospace("");
-            ui.monospace(format!("Start/split      : {}", self.split_key));
-            ui.monospace(format!("Reset            : {}", self.reset_key));
-            ui.monospace(format!("Pause            : {}", self.pause_key));
-            ui.monospace(format!("Unpause          : {}", self.unpause_key));
-            ui.monospace(format!("Switch comparison: {}", self.comparison_key));
+            for line in legend {
+                ui.monospace(line);
+            }
+            show_statistics(ui, &timer_readonly, time_format, &mut self.statistics);
             ui.monospace("");
-            ui.monospace("Note: attempts are saved when closing the application");
-            ui.monospace("Note2: reset the timer for this attempt time to be stored in the run history when you close this application.");
+            ui.monospace("Note: attempts are saved while running (unless autosave is disabled) and when closing the application");
+            ui.monospace("Note2: reset the timer for this attempt time to be stored in the run history.");
         });
 
         // continously repaint even if out of focus
@@ -304,8 +804,13 @@ impl eframe::App for SpeedrunDisplay {
     // NOTE: only called when persistence feature is enabled
     fn save(&mut self, _storage: &mut dyn Storage) {
         let timer = self.timer.read().unwrap();
-        let run = timer.run();
-        if let Err(e) = save_run_to_file(run, &self.settings) {
+        let splits = self.splits.read().unwrap();
+        if splits.is_practicing() {
+            info!("Practice mode: run not saved");
+            return;
+        }
+        let run = splits.get_saved_run(&timer);
+        if let Err(e) = save_run_to_file(&run, &self.settings) {
             error!("{e}");
         } else {
             info!("Saved run");
@@ -313,58 +818,681 @@ impl eframe::App for SpeedrunDisplay {
     }
 }
 
-/// Returns true if splits have icons to display
-fn run_has_icon(run: &livesplit_core::Run) -> &str {
-    let img_data = run.segment(0).icon().data();
-    if img_data.is_empty() {
+/// Paint every component of layout `state` using colors of `settings`
+#[cfg(feature = "gui")]
+fn show_layout_state(
+    ui: &mut egui::Ui,
+    state: &LayoutState,
+    settings: &GeneralSettings,
+    icons: &HashMap<usize, RetainedImage>,
+) {
+    let text_color = to_color32(state.text_color);
+    for (i, component) in state.components.iter().enumerate() {
+        match component {
+            ComponentState::Title(title) => show_title(ui, title, text_color),
+            ComponentState::Splits(splits) => {
+                show_layout_splits(ui, i, splits, text_color, icons);
+            }
+            ComponentState::Timer(timer) => show_timer(ui, timer),
+            ComponentState::DetailedTimer(detailed_timer) => {
+                show_detailed_timer(ui, detailed_timer, text_color);
+            }
+            ComponentState::KeyValue(key_value) => {
+                show_key_value(ui, key_value, settings, text_color);
+            }
+            ComponentState::Text(text) => show_text(ui, text, text_color),
+            ComponentState::Separator(_) => {
+                ui.separator();
+            }
+            ComponentState::BlankSpace(blank_space) => {
+                #[allow(clippy::cast_precision_loss)]
+                ui.add_space(blank_space.size as f32);
+            }
+            // NOTE: graphs are not painted
+            ComponentState::Graph(_) => {}
+        }
+    }
+}
+
+/// Paint title component (game, category and attempt count)
+#[cfg(feature = "gui")]
+fn show_title(ui: &mut egui::Ui, title: &title::State, text_color: egui::Color32) {
+    let color = title.text_color.map_or(text_color, to_color32);
+    if let Some(line1) = title.line1.last() {
+        ui.heading(egui::RichText::new(line1.as_ref()).color(color));
+    }
+    ui.horizontal(|ui| {
+        if let Some(line2) = title.line2.last() {
+            ui.label(egui::RichText::new(line2.as_ref()).color(color));
+        }
+        let attempts = match (title.finished_runs, title.attempts) {
+            (Some(finished), Some(attempts)) => format!("{finished}/{attempts}"),
+            (None, Some(attempts)) => attempts.to_string(),
+            (Some(finished), None) => finished.to_string(),
+            (None, None) => return,
+        };
+        ui.with_layout(egui::Layout::right_to_left(), |ui| {
+            ui.label(egui::RichText::new(attempts).color(color));
+        });
+    });
+}
+
+/// Paint splits component with its columns
+#[cfg(feature = "gui")]
+fn show_layout_splits(
+    ui: &mut egui::Ui,
+    id: usize,
+    splits: &splits::State,
+    text_color: egui::Color32,
+    icons: &HashMap<usize, RetainedImage>,
+) {
+    egui::Grid::new(("layout splits", id))
+        .num_columns(2)
+        .show(ui, |ui| {
+            if let Some(labels) = &splits.column_labels {
+                if splits.has_icons {
+                    ui.label("");
+                }
+                ui.label("");
+                // NOTE: columns are specified from right to left
+                for label in labels.iter().rev() {
+                    ui.label(egui::RichText::new(label).monospace().color(text_color));
+                }
+                ui.end_row();
+            }
+            for split in &splits.splits {
+                if splits.has_icons {
+                    match icons.get(&split.index) {
+                        // 27 pixels is ~= 5 whitespaces
+                        Some(img) => {
+                            ui.image(img.texture_id(ui.ctx()), egui::Vec2::new(27f32, 27f32))
+                        }
+                        None => ui.label(""),
+                    };
+                }
+                let name = egui::RichText::new(&split.name).color(text_color);
+                if split.is_current_split {
+                    ui.label(name.strong().underline());
+                } else {
+                    ui.label(name);
+                }
+                for column in split.columns.iter().rev() {
+                    ui.label(
+                        egui::RichText::new(&column.value)
+                            .monospace()
+                            .color(to_color32(column.visual_color)),
+                    );
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Paint timer component
+#[cfg(feature = "gui")]
+fn show_timer(ui: &mut egui::Ui, timer: &timer::State) {
+    #[allow(clippy::cast_precision_loss)]
+    let size = timer.height as f32 / 2.0;
+    ui.with_layout(egui::Layout::right_to_left(), |ui| {
+        // NOTE: right to left, fraction first
+        ui.label(
+            egui::RichText::new(&timer.fraction)
+                .monospace()
+                .size(size * 0.7)
+                .color(to_color32(timer.top_color)),
+        );
+        ui.label(
+            egui::RichText::new(&timer.time)
+                .monospace()
+                .size(size)
+                .color(to_color32(timer.top_color)),
+        );
+    });
+}
+
+/// Paint detailed timer component (attempt timer, segment timer and
+/// comparisons)
+#[cfg(feature = "gui")]
+fn show_detailed_timer(
+    ui: &mut egui::Ui,
+    detailed_timer: &detailed_timer::State,
+    text_color: egui::Color32,
+) {
+    if let Some(name) = &detailed_timer.segment_name {
+        ui.label(egui::RichText::new(name).color(text_color));
+    }
+    show_timer(ui, &detailed_timer.timer);
+    show_timer(ui, &detailed_timer.segment_timer);
+    for comparison in [&detailed_timer.comparison1, &detailed_timer.comparison2]
+        .into_iter()
+        .flatten()
+    {
+        ui.label(
+            egui::RichText::new(format!("{}: {}", comparison.name, comparison.time))
+                .monospace()
+                .color(text_color),
+        );
+    }
+}
+
+/// Paint key value component (previous segment, sum of best...)
+#[cfg(feature = "gui")]
+fn show_key_value(
+    ui: &mut egui::Ui,
+    key_value: &key_value::State,
+    settings: &GeneralSettings,
+    text_color: egui::Color32,
+) {
+    let key_color = key_value.key_color.map_or(text_color, to_color32);
+    let value_color = match key_value.value_color {
+        Some(c) => to_color32(c),
+        None => to_color32(key_value.semantic_color.visualize(settings)),
+    };
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(&key_value.key).color(key_color));
+        ui.with_layout(egui::Layout::right_to_left(), |ui| {
+            ui.label(
+                egui::RichText::new(&key_value.value)
+                    .monospace()
+                    .color(value_color),
+            );
+        });
+    });
+}
+
+/// Paint text component
+#[cfg(feature = "gui")]
+fn show_text(ui: &mut egui::Ui, text: &text::State, text_color: egui::Color32) {
+    let left_center_color = text.left_center_color.map_or(text_color, to_color32);
+    match &text.text {
+        text::TextState::Center(center) => {
+            ui.vertical_centered(|ui| {
+                ui.label(egui::RichText::new(center).color(left_center_color));
+            });
+        }
+        text::TextState::Split(left, right) => {
+            let right_color = text.right_color.map_or(text_color, to_color32);
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(left).color(left_center_color));
+                ui.with_layout(egui::Layout::right_to_left(), |ui| {
+                    ui.label(egui::RichText::new(right).color(right_color));
+                });
+            });
+        }
+    }
+}
+
+/// Statistics of past attempts, with the run version and timing method they
+/// were computed for
+#[cfg(feature = "gui")]
+type CachedStatistics = (((u32, usize), TimingMethod), RunStatistics);
+
+/// Paint collapsed statistics of past attempts of the run of `timer`.
+/// Statistics are kept in `cache` until the run or timing method changes
+///
+/// NOTE: statistics are only computed while the section is open
+#[cfg(feature = "gui")]
+fn show_statistics(
+    ui: &mut egui::Ui,
+    timer: &Timer,
+    time_format: TimeFormat,
+    cache: &mut Option<CachedStatistics>,
+) {
+    ui.collapsing("Statistics", |ui| {
+        let timing_method = timer.current_timing_method();
+        let version = (run_version(timer), timing_method);
+        if cache.as_ref().map(|(v, _)| *v) != Some(version) {
+            *cache = Some((version, RunStatistics::new(timer.run(), timing_method)));
+        }
+        if let Some((_, statistics)) = cache {
+            for line in statistics.report(time_format) {
+                ui.monospace(line);
+            }
+        }
+    });
+}
+
+/// Load icons that changed since last layout update
+///
+/// NOTE: icons are only provided by the splits component when first shown or
+///       changed
+#[cfg(feature = "gui")]
+fn update_layout_icons(icons: &mut HashMap<usize, RetainedImage>, state: &LayoutState) {
+    for component in &state.components {
+        if let ComponentState::Splits(splits) = component {
+            for change in &splits.icon_changes {
+                if change.icon.is_empty() {
+                    icons.remove(&change.segment_index);
+                    continue;
+                }
+                match RetainedImage::from_image_bytes(
+                    format!("segment {}", change.segment_index),
+                    &change.icon,
+                ) {
+                    Ok(image) => {
+                        icons.insert(change.segment_index, image);
+                    }
+                    Err(e) => error!("{e}"),
+                }
+            }
+        }
+    }
+}
+
+/// Converts livesplit `color` to egui color
+#[cfg(feature = "gui")]
+fn to_color32(color: Color) -> egui::Color32 {
+    let [r, g, b, a] = color.to_rgba8();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Returns a single color approximating `gradient`
+#[cfg(feature = "gui")]
+fn gradient_color(gradient: Gradient) -> egui::Color32 {
+    match gradient {
+        Gradient::Transparent => egui::Color32::TRANSPARENT,
+        Gradient::Plain(c) | Gradient::Vertical(c, _) | Gradient::Horizontal(c, _) => to_color32(c),
+    }
+}
+
+/// Returns icons of segments of `run` that have one, indexed by segment
+///
+/// # Errors
+/// Returns an error if an icon cannot be decoded
+#[cfg(feature = "gui")]
+fn load_icons(run: &Run) -> Result<HashMap<usize, RetainedImage>, String> {
+    let mut icons = HashMap::new();
+    for (i, segment) in run.segments().iter().enumerate() {
+        let img_data = segment.icon().data();
+        if !img_data.is_empty() {
+            icons.insert(
+                i,
+                RetainedImage::from_image_bytes(segment.name(), img_data)?,
+            );
+        }
+    }
+    Ok(icons)
+}
+
+/// Paint icon of split `i`, or an empty space of the same size if another
+/// split has an icon
+#[cfg(feature = "gui")]
+fn show_icon(
+    ui: &mut egui::Ui,
+    ctx: &egui::Context,
+    icons: &HashMap<usize, RetainedImage>,
+    i: usize,
+) {
+    // example: https://github.com/emilk/egui/blob/0.17.0/eframe/examples/image.rs
+    // 27 pixels is ~= 5 whitespaces
+    let dimensions = egui::Vec2::new(27f32, 27f32);
+    match icons.get(&i) {
+        //ui.image(image.texture_id(ctx), image.size_vec2());
+        Some(img) => {
+            ui.image(img.texture_id(ctx), dimensions);
+        }
+        None if !icons.is_empty() => {
+            ui.allocate_space(dimensions);
+        }
+        None => {}
+    }
+}
+
+/// Returns padding of rows without icon, if any split has an icon to display
+#[cfg(feature = "gui")]
+fn icon_padding(icons: &HashMap<usize, RetainedImage>) -> &'static str {
+    if icons.is_empty() {
         ""
     } else {
         "     "
     }
 }
 
-/// Formats `timespan` to "hh:mm:ss.ms"
-fn format_timespan<'a>(time: TimeSpan) -> Result<String, UIError<'a>> {
-    let d = time.to_duration();
-    if d.is_negative() {
-        return Err(UIError::Other(
-            "Usage of negative time in function that only accepts positive time".to_string(),
+/// Returns name and value of enabled summary `rows` for current state of
+/// `timer`, formatted with `time_format`
+pub(crate) fn summary_rows(
+    timer: &Timer,
+    rows: SummaryRows,
+    time_format: TimeFormat,
+) -> Vec<(&'static str, String)> {
+    let snapshot = timer.snapshot();
+    let timing_method = timer.current_timing_method();
+    let comparison = timer.current_comparison();
+    let mut summary = vec![];
+    if rows.sum_of_best {
+        let sum_of_best =
+            sum_of_segments::calculate_best(timer.run().segments(), false, true, timing_method);
+        summary.push(("Sum of best", format_time(sum_of_best, time_format)));
+    }
+    if rows.best_possible_time {
+        let (best_possible_time, _) = current_pace::calculate(&snapshot, best_segments::NAME);
+        summary.push((
+            "Best possible time",
+            format_time(best_possible_time, time_format),
         ));
     }
-    Ok(format!(
-        "{:02}:{:02}:{:02}.{:03}",
-        // TODO optionnal day/week formatting
-        d.whole_hours().rem_euclid(24),
-        d.whole_minutes().rem_euclid(60),
-        d.whole_seconds().rem_euclid(60),
-        d.whole_milliseconds().rem_euclid(1000)
-    ))
-}
-
-/// Format `timespan` to hh:mm:ss.ms and shorten displayed text when possible
-fn format_timesave(timespan: TimeSpan) -> String {
-    let duration = timespan.to_duration();
-    let h = duration.whole_hours() % 24;
-    let m = duration.whole_minutes() % 60;
-    // NOTE: not using abs will print out '-' in case of a time save
-    let s = format!(
-        "{:02}:{:02}:{:02}.{:03}",
-        // TODO optionnal day/week formatting
-        h.abs(),
-        m.abs(),
-        (duration.whole_seconds() % 60).abs(),
-        (duration.whole_milliseconds() % 1000).abs()
-    );
-
-    // always print seconds but remove minutes and hours if 0
-    if h == 0 {
-        let (_, l) = s.split_at(3);
-        if m == 0 {
-            let (_, l) = l.split_at(3);
-            return l.to_string();
+    // NOTE: current split index is out of range when the run has ended
+    let split_index = timer
+        .current_split_index()
+        .filter(|i| *i < timer.run().len());
+    if rows.possible_time_save {
+        let time_save = split_index
+            .and_then(|i| possible_time_save::calculate(&snapshot, i, comparison, false).0);
+        summary.push(("Possible time save", format_time(time_save, time_format)));
+    }
+    if rows.previous_segment {
+        let live_segment =
+            analysis::check_live_delta(&snapshot, false, comparison, timing_method).is_some();
+        let delta = match timer.current_split_index() {
+            Some(i) if live_segment => {
+                analysis::live_segment_delta(&snapshot, i, comparison, timing_method)
+            }
+            Some(i) => i.checked_sub(1).and_then(|previous| {
+                analysis::previous_segment_delta(timer, previous, comparison, timing_method)
+            }),
+            None => None,
+        };
+        let name = if live_segment {
+            "Live segment"
+        } else {
+            "Previous segment"
+        };
+        summary.push((name, format_delta(delta, time_format)));
+    }
+    summary
+}
+
+/// Returns RGB color of each delta class from `colors`. Invalid colors are
+/// replaced with the default ones
+pub(crate) fn delta_palette(colors: &DeltaColors) -> HashMap<DeltaClass, [u8; 3]> {
+    let defaults = DeltaColors::default();
+    [
+        (DeltaClass::Gold, &colors.gold, &defaults.gold),
+        (
+            DeltaClass::AheadGaining,
+            &colors.ahead_gaining,
+            &defaults.ahead_gaining,
+        ),
+        (
+            DeltaClass::AheadLosing,
+            &colors.ahead_losing,
+            &defaults.ahead_losing,
+        ),
+        (
+            DeltaClass::BehindGaining,
+            &colors.behind_gaining,
+            &defaults.behind_gaining,
+        ),
+        (
+            DeltaClass::BehindLosing,
+            &colors.behind_losing,
+            &defaults.behind_losing,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(class, color, default)| {
+        let rgb = parse_hex_color(color).or_else(|| {
+            warn!("Invalid color \"{color}\" for {class:?}, using \"{default}\"");
+            parse_hex_color(default)
+        });
+        rgb.map(|rgb| (class, rgb))
+    })
+    .collect()
+}
+
+/// Parse `color` formatted as "#RRGGBB"
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Returns keybinding legend lines of `keybinding`
+pub(crate) fn keybinding_legend(keybinding: Keybinding) -> Vec<String> {
+    vec![
+        format!("Start/split      : {:?}", keybinding.split_key),
+        format!("Reset            : {:?}", keybinding.reset_key),
+        format!("Pause            : {:?}", keybinding.pause_key),
+        format!("Unpause          : {:?}", keybinding.unpause_key),
+        format!("Switch comparison: {:?}", keybinding.comparison_key),
+        format!(
+            "Undo split       : {}",
+            format_optional_key(keybinding.undo_split_key)
+        ),
+        format!(
+            "Skip split       : {}",
+            format_optional_key(keybinding.skip_split_key)
+        ),
+        format!(
+            "Timing method    : {}",
+            format_optional_key(keybinding.timing_method_key)
+        ),
+        format!(
+            "Segment times    : {}",
+            format_optional_key(keybinding.segment_times_key)
+        ),
+        format!(
+            "Practice mode    : {}",
+            format_optional_key(keybinding.practice_key)
+        ),
+        format!(
+            "Game time pause  : {}",
+            format_optional_key(keybinding.game_time_pause_key)
+        ),
+    ]
+}
+
+/// Returns `comparison_name` truncated to fit the splits header
+pub(crate) fn truncate_comparison_name(comparison_name: &str) -> String {
+    if comparison_name.len() >= 13 {
+        let (l, _) = comparison_name.split_at(10);
+        format!("{l}...")
+    } else {
+        comparison_name.to_string()
+    }
+}
+
+/// Returns displayed name of `timing_method`
+pub(crate) fn timing_method_name(timing_method: TimingMethod) -> &'static str {
+    match timing_method {
+        TimingMethod::RealTime => "Real time",
+        TimingMethod::GameTime => "Game time",
+    }
+}
+
+/// Formats optional `key` for keybinding legend
+fn format_optional_key(key: Option<KeyCode>) -> String {
+    match key {
+        Some(k) => format!("{k:?}"),
+        None => "not bound".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{split_at, timer};
+
+    /// Returns timer of a run with segments "Intro" and "End" using game time
+    fn game_timer() -> Timer {
+        timer(&["Intro", "End"], TimingMethod::GameTime)
+    }
+
+    #[test]
+    fn summary_rows_are_computed_for_enabled_rows() {
+        let mut timer = game_timer();
+        split_at(&mut timer, &[40.0, 100.0]);
+        timer.reset(true);
+        split_at(&mut timer, &[30.0, 95.0]);
+        timer.reset(true);
+        let time_format = TimeFormat::default();
+
+        let rows = summary_rows(&timer, SummaryRows::default(), time_format);
+
+        let names: Vec<&str> = rows.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            [
+                "Sum of best",
+                "Best possible time",
+                "Possible time save",
+                "Previous segment"
+            ]
+        );
+        assert_eq!(
+            rows[0].1,
+            time_format.format_time(TimeSpan::from_seconds(90.0))
+        );
+        assert_eq!(rows[2].1, "-");
+
+        let rows = SummaryRows {
+            sum_of_best: false,
+            possible_time_save: false,
+            ..SummaryRows::default()
+        };
+        let names: Vec<&str> = summary_rows(&timer, rows, time_format)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["Best possible time", "Previous segment"]);
+    }
+
+    /// Returns splits display of `timer` updated with its split times
+    fn splits_of(timer: &Timer) -> Splits {
+        let names = timer
+            .run()
+            .segments()
+            .iter()
+            .map(|s| s.name().to_string())
+            .collect();
+        let mut splits = Splits::new(names, TimeFormat::default());
+        let comparison = timer.current_comparison();
+        for (i, segment) in timer.run().segments().iter().enumerate() {
+            splits.update_split(
+                i,
+                segment.split_time().game_time.unwrap_or_default(),
+                segment.comparison(comparison).game_time.unwrap_or_default(),
+            );
         }
-        return l.to_string();
+        splits.update_analysis(timer);
+        splits
+    }
+
+    #[test]
+    fn hex_colors_are_parsed() {
+        assert_eq!(parse_hex_color("#D8AF1F"), Some([216, 175, 31]));
+        assert_eq!(parse_hex_color("#00cc36"), Some([0, 204, 54]));
+        assert_eq!(parse_hex_color("D8AF1F"), None);
+        assert_eq!(parse_hex_color("#D8AF1"), None);
+        assert_eq!(parse_hex_color("#GGAF1F"), None);
+        assert_eq!(parse_hex_color("#D8AF\u{e9}"), None);
     }
 
-    s
+    #[test]
+    fn invalid_delta_colors_use_defaults() {
+        let colors = DeltaColors {
+            gold: "gold".to_string(),
+            ahead_gaining: "#010203".to_string(),
+            ..DeltaColors::default()
+        };
+
+        let palette = delta_palette(&colors);
+
+        assert_eq!(palette[&DeltaClass::Gold], [216, 175, 31]);
+        assert_eq!(palette[&DeltaClass::AheadGaining], [1, 2, 3]);
+        assert!(!palette.contains_key(&DeltaClass::None));
+    }
+
+    #[test]
+    fn best_segment_is_gold() {
+        let mut timer = game_timer();
+        split_at(&mut timer, &[40.0, 100.0]);
+        timer.reset(true);
+        split_at(&mut timer, &[30.0, 105.0]);
+
+        let splits = splits_of(&timer);
+
+        assert_eq!(splits.get_delta_class(0), DeltaClass::Gold);
+        assert_eq!(splits.get_delta_class(1), DeltaClass::BehindLosing);
+    }
+
+    #[test]
+    fn segment_deltas_are_classified() {
+        assert_eq!(segment_delta_class(true, true), DeltaClass::AheadGaining);
+        assert_eq!(segment_delta_class(true, false), DeltaClass::AheadLosing);
+        assert_eq!(segment_delta_class(false, true), DeltaClass::BehindGaining);
+        assert_eq!(segment_delta_class(false, false), DeltaClass::BehindLosing);
+    }
+
+    #[test]
+    fn segment_times_replace_split_times_on_demand() {
+        let mut timer = game_timer();
+        split_at(&mut timer, &[40.0, 100.0]);
+        timer.reset(true);
+        split_at(&mut timer, &[30.0, 105.0]);
+        let mut splits = splits_of(&timer);
+        let time_format = TimeFormat::default();
+        let seconds = |seconds| TimeSpan::from_seconds(seconds);
+        assert_eq!(
+            splits.get_row(1),
+            (
+                time_format.format_time(seconds(105.0)),
+                time_format.format_time(seconds(100.0)),
+                time_format.format_delta(seconds(5.0)),
+                DeltaClass::BehindLosing
+            )
+        );
+        assert!(splits.get_best_segment_column(1).is_none());
+        assert_eq!(splits.get_best_segment_header(), "");
+
+        splits.toggle_segment_times();
+
+        assert_eq!(splits.get_time_header(), "Segment time");
+        assert_eq!(
+            splits.get_row(1),
+            (
+                time_format.format_time(seconds(75.0)),
+                time_format.format_time(seconds(60.0)),
+                time_format.format_delta(seconds(15.0)),
+                DeltaClass::BehindLosing
+            )
+        );
+        assert_eq!(
+            splits.get_best_segment_column(1),
+            Some((time_format.format_delta(seconds(15.0)), DeltaClass::None))
+        );
+        assert_eq!(splits.get_row(0).3, DeltaClass::Gold);
+        assert_eq!(
+            splits.get_best_segment_column(0),
+            Some((time_format.format_delta(seconds(-10.0)), DeltaClass::Gold))
+        );
+
+        splits.toggle_segment_times();
+
+        assert_eq!(splits.get_time_header(), "Current time");
+        assert_eq!(splits.get_row(1).0, time_format.format_time(seconds(105.0)));
+    }
+
+    #[test]
+    #[cfg(feature = "gui")]
+    fn icons_are_indexed_by_split() {
+        let mut run = game_timer().run().clone();
+        assert!(load_icons(&run).unwrap().is_empty());
+        assert_eq!(icon_padding(&HashMap::new()), "");
+
+        let mut icon = std::io::Cursor::new(vec![]);
+        image::DynamicImage::new_rgba8(8, 8)
+            .write_to(&mut icon, image::ImageOutputFormat::Png)
+            .unwrap();
+        run.segment_mut(1)
+            .set_icon(livesplit_core::settings::Image::new(icon.get_ref()));
+
+        let icons = load_icons(&run).unwrap();
+        assert!(!icons.contains_key(&0));
+        assert!(icons.contains_key(&1));
+        assert_eq!(icon_padding(&icons), "     ");
+    }
 }